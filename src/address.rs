@@ -0,0 +1,43 @@
+/// I2C address of a NeoTrellis.
+///
+/// The board answers at 0x2E, each bridged address jumper `A0` to `A3` adds 1, 2,
+/// 4 or 8 to that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address(u8);
+
+impl Address {
+  /// Address without any jumper bridged.
+  pub const DEFAULT: Self = Self(0x2E);
+
+  pub const fn new(address: u8) -> Self {
+    Self(address)
+  }
+
+  /// Address of a board whose jumpers are bridged as given, e.g.
+  /// `Address::with_jumpers(true, false, true, false)` for A0 and A2 (0x33).
+  pub const fn with_jumpers(a0: bool, a1: bool, a2: bool, a3: bool) -> Self {
+    Self(Self::DEFAULT.0 + a0 as u8 + 2 * a1 as u8 + 4 * a2 as u8 + 8 * a3 as u8)
+  }
+
+  pub const fn value(self) -> u8 {
+    self.0
+  }
+}
+
+impl Default for Address {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+impl From<u8> for Address {
+  fn from(address: u8) -> Self {
+    Self(address)
+  }
+}
+
+impl From<Address> for u8 {
+  fn from(address: Address) -> Self {
+    address.0
+  }
+}
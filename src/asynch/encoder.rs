@@ -0,0 +1,138 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use super::Seesaw;
+use crate::{Address, Error, Module};
+
+const GPIO_DIRCLR_BULK: u8 = 0x03;
+const GPIO_BULK: u8 = 0x04;
+const GPIO_BULK_SET: u8 = 0x05;
+const GPIO_PULLENSET: u8 = 0x0B;
+/// Pins of the 32 bit GPIO bulk registers.
+const GPIO_PINS: u8 = 32;
+
+const ENCODER_INTENSET: u8 = 0x10;
+const ENCODER_INTENCLR: u8 = 0x20;
+const ENCODER_POSITION: u8 = 0x30;
+const ENCODER_DELTA: u8 = 0x40;
+
+/// Driver for the seesaw rotary encoder boards, e.g. the Adafruit I2C QT Rotary
+/// Encoder or the ANO directional navigation and scroll wheel.
+pub struct Encoder<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  seesaw: Seesaw<I2C, D>,
+  button_pin: u8,
+}
+
+impl<I2C, D> Encoder<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Address of the QT Rotary Encoder without address jumpers.
+  pub const DEFAULT_ADDRESS: Address = Address::new(0x36);
+  /// Seesaw pin of the push button of the QT Rotary Encoder.
+  pub const QT_ROTARY_BUTTON_PIN: u8 = 24;
+  /// Seesaw pin of the center button of the ANO wheel.
+  pub const ANO_SELECT_PIN: u8 = 1;
+
+  /// Resets the board at `address` and sets up `button_pin` as an input with
+  /// pull-up. Fails with `Error::OutOfBounds` without any bus traffic if there is no
+  /// such pin.
+  pub async fn new(
+    bus: I2C,
+    address: Address,
+    delay: D,
+    button_pin: u8,
+  ) -> Result<Self, Error<I2C::Error>> {
+    if button_pin >= GPIO_PINS {
+      return Err(Error::OutOfBounds);
+    }
+    let mut encoder = Self {
+      seesaw: Seesaw::new(bus, address, delay),
+      button_pin,
+    };
+    encoder.seesaw.soft_reset().await?;
+
+    let mask = (1u32 << button_pin).to_be_bytes();
+    let gpio = Module::Gpio.into();
+    encoder.seesaw.write(gpio, GPIO_DIRCLR_BULK, &mask).await?;
+    encoder.seesaw.write(gpio, GPIO_PULLENSET, &mask).await?;
+    encoder.seesaw.write(gpio, GPIO_BULK_SET, &mask).await?;
+
+    Ok(encoder)
+  }
+
+  /// Absolute position in detents, counting up clockwise.
+  pub async fn position(&mut self) -> Result<i32, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self
+      .seesaw
+      .read(Module::Encoder.into(), ENCODER_POSITION, &mut value)
+      .await?;
+
+    Ok(i32::from_be_bytes(value))
+  }
+
+  pub async fn set_position(&mut self, position: i32) -> Result<(), Error<I2C::Error>> {
+    self
+      .seesaw
+      .write(
+        Module::Encoder.into(),
+        ENCODER_POSITION,
+        &position.to_be_bytes(),
+      )
+      .await
+  }
+
+  /// Detents turned since the last call, clockwise positive.
+  pub async fn delta(&mut self) -> Result<i32, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self
+      .seesaw
+      .read(Module::Encoder.into(), ENCODER_DELTA, &mut value)
+      .await?;
+
+    Ok(i32::from_be_bytes(value))
+  }
+
+  /// Whether the button is held down.
+  pub async fn button(&mut self) -> Result<bool, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self
+      .seesaw
+      .read(Module::Gpio.into(), GPIO_BULK, &mut value)
+      .await?;
+
+    // The button pulls the pin low
+    Ok(u32::from_be_bytes(value) & (1 << self.button_pin) == 0)
+  }
+
+  /// Makes the board pull its INT line low when the encoder is turned.
+  pub async fn enable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self
+      .seesaw
+      .write(Module::Encoder.into(), ENCODER_INTENSET, &[0x01])
+      .await
+  }
+
+  pub async fn disable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self
+      .seesaw
+      .write(Module::Encoder.into(), ENCODER_INTENCLR, &[0x01])
+      .await
+  }
+
+  /// The seesaw transport, e.g. for the other buttons of the ANO wheel.
+  pub fn seesaw(&mut self) -> &mut Seesaw<I2C, D> {
+    &mut self.seesaw
+  }
+
+  /// Gives back the bus and the delay.
+  pub fn free(self) -> (I2C, D) {
+    self.seesaw.free()
+  }
+}
@@ -0,0 +1,57 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use super::NeoTrellis;
+use crate::keypad::FifoBuffer;
+use crate::{Error, HasKeypad, KeypadEvent, Ready};
+
+/// Reader of the events pending in the keypad FIFO, see `NeoTrellis::events`.
+///
+/// The FIFO is read on the first call to `next`, the events are then decoded one at
+/// a time. A bus or protocol error is returned once and ends the reading.
+pub struct EventReader<'a, I2C, D, S = Ready>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  trellis: &'a mut NeoTrellis<I2C, D, S>,
+  buffer: FifoBuffer,
+  fetched: bool,
+}
+
+impl<'a, I2C, D, S> EventReader<'a, I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  pub(crate) fn new(trellis: &'a mut NeoTrellis<I2C, D, S>) -> Self {
+    Self {
+      trellis,
+      buffer: FifoBuffer::new(),
+      fetched: false,
+    }
+  }
+
+  async fn fetch(&mut self) -> Result<(), Error<I2C::Error>> {
+    let length = self.trellis.read_fifo(self.buffer.bytes_mut()).await?;
+    self.buffer.set_length(length);
+
+    Ok(())
+  }
+
+  /// The next pending event, `None` once all events of the read were returned.
+  pub async fn next(&mut self) -> Option<Result<KeypadEvent, Error<I2C::Error>>> {
+    if !self.fetched {
+      self.fetched = true;
+      if let Err(e) = self.fetch().await {
+        return Some(Err(e));
+      }
+    }
+
+    self
+      .buffer
+      .next_event(self.trellis.seesaw.address().value(), &self.trellis.keymap)
+  }
+}
@@ -0,0 +1,1353 @@
+//! Async driver built on the `embedded-hal-async` traits.
+//!
+//! Mirrors the blocking [`NeoTrellis`](crate::NeoTrellis) and
+//! [`MultiTrellis`](crate::MultiTrellis) API, but every delay is awaited so the
+//! reset wait and register-read delays don't block the executor.
+
+mod eeprom;
+mod encoder;
+mod events;
+mod neopixel;
+mod pwm;
+mod seesaw;
+mod touch;
+mod uart;
+
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use heapless::Vec;
+
+pub use self::encoder::Encoder;
+pub use self::events::EventReader;
+pub use self::neopixel::SeesawNeoPixel;
+pub use self::seesaw::Seesaw;
+use crate::faults::Faults;
+use crate::keypad::{
+  configure_commands, decode_event, decode_fifo, encode_key_commands, key_commands,
+  key_event_command, keypad_commands, KEY_COMMANDS_PER_WRITE, MAX_KEY_COMMAND,
+};
+use crate::orientation::KeyMap;
+use crate::pixels::{pixel_writes, PixelBuffer};
+use crate::settings::{SETTINGS_LENGTH, SETTINGS_OFFSET};
+use crate::{
+  Address, BoardStatus, Capabilities, Color, ColorOrder, Config, Error, Event, EventProducer,
+  EventSet, FirmwareVersion, Framebuffer, HardwareId, HasKeypad, HasNeopixel, Key, KeypadEvent,
+  KeypadOnly, Layout, Module, MultiEvent, NeopixelOnly, Ready, RetryPolicy, Rotation, Settings,
+  Speed, State, Stats, Temperature, Uninitialized, KEYPAD_COUNT, KEYPAD_EVENT, KEYPAD_FIFO,
+  KEYPAD_INTENCLR, KEYPAD_INTENSET, NEOPIXEL_BUF, NEOPIXEL_BUF_LENGTH, NEOPIXEL_PIN, NEOPIXEL_SHOW,
+  NEOPIXEL_SPEED, NEOTRELLIS_ADDRESSES, NEOTRELLIS_NEOPIXEL_PIN,
+};
+
+/// A `W` x `H` array of boards addressed as one continuous coordinate space.
+///
+/// By default `trellis[x][y]` is the board covering keys `4 * x..4 * x + 4`
+/// horizontally and `4 * y..4 * y + 4` vertically, a `Layout` can place the boards
+/// differently.
+pub struct MultiTrellis<I2C, D, const W: usize, const H: usize>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  pub trellis: [[NeoTrellis<I2C, D>; H]; W],
+  layout: Layout<W, H>,
+  faults: Faults<W, H>,
+}
+
+pub struct NeoTrellis<I2C, D, S = Ready>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  seesaw: Seesaw<I2C, D>,
+  neopixel_pin: u8,
+  pixels: PixelBuffer,
+  keymap: KeyMap,
+  speed: Speed,
+  interrupt: bool,
+  auto_recover: Option<u8>,
+  key_events: EventSet,
+  /// Physical keys that report no events.
+  disabled_keys: u16,
+  state: PhantomData<S>,
+}
+
+impl<I2, D, const W: usize, const H: usize> MultiTrellis<I2, D, W, H>
+where
+  I2: I2c,
+  D: DelayNs,
+{
+  /// Places `trellis[x][y]` at `(4 * x, 4 * y)`, see `Layout::grid`.
+  pub fn new(trellis: [[NeoTrellis<I2, D>; H]; W]) -> Self {
+    Self::with_layout(trellis, Layout::grid())
+  }
+
+  pub fn with_layout(trellis: [[NeoTrellis<I2, D>; H]; W], layout: Layout<W, H>) -> Self {
+    Self {
+      trellis,
+      layout,
+      faults: Faults::new(),
+    }
+  }
+
+  /// Changes where the boards sit. The grid rotates the coordinates of each board
+  /// as its placement says, on top of any rotation set on the board itself.
+  pub fn set_layout(&mut self, layout: Layout<W, H>) {
+    self.layout = layout;
+  }
+
+  pub fn layout(&self) -> &Layout<W, H> {
+    &self.layout
+  }
+
+  pub fn into_inner(self) -> [[NeoTrellis<I2, D>; H]; W] {
+    self.trellis
+  }
+
+  /// In fault tolerant mode a board that fails is marked as faulted and skipped
+  /// from then on, instead of failing the operation on the whole grid. Off by
+  /// default.
+  pub fn set_fault_tolerant(&mut self, enable: bool) {
+    self.faults.set_tolerant(enable);
+  }
+
+  pub fn fault_tolerant(&self) -> bool {
+    self.faults.tolerant()
+  }
+
+  /// Status of `trellis[x][y]` after the last operation on it.
+  pub fn board_status(&self, x: usize, y: usize) -> BoardStatus {
+    self.faults.status(x, y)
+  }
+
+  /// Grid positions of the faulted boards.
+  pub fn faulted_boards(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.faults.faulted()
+  }
+
+  /// Marks all boards as healthy again, so fault tolerant mode stops skipping them.
+  pub fn clear_faults(&mut self) {
+    self.faults.clear();
+  }
+
+  /// Checks which boards respond, for setups where boards are plugged in and out.
+  ///
+  /// A faulted board that responds again is recovered, see `NeoTrellis::recover`,
+  /// and becomes healthy. A healthy board that doesn't respond is marked as
+  /// faulted. `handler` is called with the position and new status of every board
+  /// that changed, the number of changes is returned.
+  pub async fn rescan<F: FnMut(usize, usize, BoardStatus)>(&mut self, mut handler: F) -> usize {
+    let mut changes = 0;
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        let present = trellis.is_present().await;
+        let status = match self.faults.status(x, y) {
+          BoardStatus::Faulted if present && trellis.recover(true).await.is_ok() => BoardStatus::Ok,
+          BoardStatus::Ok if !present => BoardStatus::Faulted,
+          _ => continue,
+        };
+
+        self.faults.set(x, y, status);
+        handler(x, y, status);
+        changes += 1;
+      }
+    }
+
+    changes
+  }
+
+  /// Sets the automatic recovery of every board, see `NeoTrellis::set_auto_recover`.
+  pub fn set_auto_recover(&mut self, errors: Option<u8>) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_auto_recover(errors);
+      }
+    }
+  }
+
+  /// Bus traffic of all boards together, see `NeoTrellis::stats`.
+  pub fn stats(&self) -> Stats {
+    self
+      .trellis
+      .iter()
+      .flatten()
+      .fold(Stats::new(), |stats, trellis| stats + trellis.stats())
+  }
+
+  pub fn reset_stats(&mut self) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.reset_stats();
+      }
+    }
+  }
+
+  /// Sets the retry policy of every board, see `NeoTrellis::set_retry_policy`.
+  pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_retry_policy(policy);
+      }
+    }
+  }
+
+  /// Sets the color of the LED at `index`, fails with `Error::OutOfBounds` if no
+  /// board covers `index`.
+  pub fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].set_led_color(i, color)
+  }
+
+  pub async fn show(&mut self) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.show_led().await)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Sets the brightness of every board, see `NeoTrellis::set_brightness`.
+  pub fn set_brightness(&mut self, brightness: u8) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_brightness(brightness);
+      }
+    }
+  }
+
+  /// Sets the color order of every board, see `NeoTrellis::set_color_order`.
+  pub async fn set_color_order(&mut self, order: ColorOrder) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self
+            .faults
+            .check(x, y, trellis.set_color_order(order).await)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Enables gamma correction on every board, see `NeoTrellis::set_gamma_correction`.
+  pub fn set_gamma_correction(&mut self, enable: bool) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_gamma_correction(enable);
+      }
+    }
+  }
+
+  /// Sets the brightness of the LED at `index`, see `NeoTrellis::set_pixel_brightness`.
+  /// Fails with `Error::OutOfBounds` if no board covers `index`.
+  pub fn set_pixel_brightness(
+    &mut self,
+    index: (u8, u8),
+    brightness: u8,
+  ) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].set_pixel_brightness(i, brightness)
+  }
+
+  /// Sets every LED of every board to `color`, applied by the next `show`.
+  pub fn fill(&mut self, color: Color) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.fill(color);
+      }
+    }
+  }
+
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Fills every board and shows it right away, e.g. `fill_and_show(Color::BLACK)`
+  /// to turn the whole grid off from an error handler.
+  pub async fn fill_and_show(&mut self, color: Color) -> Result<(), Error<I2::Error>> {
+    self.fill(color);
+    self.show().await
+  }
+
+  /// Writes the framebuffer to every board, then shows all of them. Each board
+  /// takes the pixels covered by its placement, pixels outside of the framebuffer
+  /// are turned off.
+  pub async fn flush<const FW: usize, const FH: usize>(
+    &mut self,
+    framebuffer: &Framebuffer<FW, FH>,
+  ) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        trellis.set_led_colors(&self.layout.board_colors(x, y, framebuffer))?;
+      }
+    }
+
+    self.show().await
+  }
+
+  /// Stops the key at `index` from reporting any event, see
+  /// `NeoTrellis::disable_key`.
+  pub async fn disable_key(&mut self, index: (u8, u8)) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].disable_key(Key::from_index(i)).await
+  }
+
+  pub async fn enable_key(&mut self, index: (u8, u8)) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].enable_key(Key::from_index(i)).await
+  }
+
+  pub fn key_enabled(&self, index: (u8, u8)) -> bool {
+    match self.layout.locate_index(index) {
+      Some((tx, ty, i)) => self.trellis[tx][ty].key_enabled(Key::from_index(i)),
+      None => false,
+    }
+  }
+
+  /// Makes every key of every board report exactly `events`, see
+  /// `NeoTrellis::set_key_events`.
+  pub async fn set_key_events(&mut self, events: EventSet) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self
+            .faults
+            .check(x, y, trellis.set_key_events(events).await)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Enables the keypad interrupt of every board, their open-drain INT lines can
+  /// be wired together to a single input.
+  pub async fn enable_interrupt(&mut self) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.enable_interrupt().await)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  pub async fn disable_interrupt(&mut self) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.disable_interrupt().await)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Drains the FIFO of every board, calling `handler` with each event in grid
+  /// coordinates. Returns the number of events handled.
+  pub async fn drain_events<F: FnMut(MultiEvent)>(
+    &mut self,
+    mut handler: F,
+  ) -> Result<usize, Error<I2::Error>> {
+    let mut handled = 0;
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if self.faults.skip(x, y) {
+          continue;
+        }
+        let placement = self.layout.placement(x, y);
+        let result = trellis
+          .drain_events(|e| handler(MultiEvent::from_placement(placement, e)))
+          .await;
+        handled += self.faults.check(x, y, result)?.unwrap_or(0);
+      }
+    }
+
+    Ok(handled)
+  }
+
+  /// Reads the pending events of all boards into `events`, one board after the
+  /// other, in global coordinates.
+  ///
+  /// The events are stored at the start of `events` and the remaining slots are set
+  /// to `None`. Events that don't fit stay in the FIFO of their board for the next
+  /// call. Returns the number of events read.
+  pub async fn read_events(
+    &mut self,
+    events: &mut [Option<MultiEvent>],
+  ) -> Result<usize, Error<I2::Error>> {
+    let mut count = 0;
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        let room = (events.len() - count).min(32);
+        if room == 0 {
+          break;
+        }
+
+        if self.faults.skip(x, y) {
+          continue;
+        }
+
+        let mut board_events = [None; 32];
+        let result = trellis.read_key_events(&mut board_events[..room]).await;
+        let read = self.faults.check(x, y, result)?.unwrap_or(0);
+
+        let placement = self.layout.placement(x, y);
+        for (slot, e) in events[count..]
+          .iter_mut()
+          .zip(board_events[..read].iter().flatten())
+        {
+          *slot = Some(MultiEvent::from_placement(placement, *e));
+        }
+        count += read;
+      }
+    }
+    for event in events[count..].iter_mut() {
+      *event = None;
+    }
+
+    Ok(count)
+  }
+}
+
+impl<I2C, D> NeoTrellis<I2C, D, Uninitialized>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Creates the driver without any bus traffic, `init` then resets and configures
+  /// the board. The reset behavior and the retry policy can be set up in between.
+  pub fn uninitialized(bus: I2C, address: Address, delay: D) -> Self {
+    Self {
+      seesaw: Seesaw::new(bus, address, delay),
+      neopixel_pin: NEOTRELLIS_NEOPIXEL_PIN,
+      pixels: PixelBuffer::new(),
+      keymap: KeyMap::new(),
+      speed: Speed::Khz800,
+      interrupt: false,
+      auto_recover: None,
+      key_events: EventSet::EDGES,
+      disabled_keys: 0,
+      state: PhantomData,
+    }
+  }
+
+  /// Sets the events the keypad reports for every key once `init` or `init_keypad`
+  /// set it up, rising and falling edges by default.
+  pub fn with_key_events(mut self, events: EventSet) -> Self {
+    self.key_events = events;
+    self
+  }
+
+  /// Resets and configures the board, the driver is ready to use afterwards.
+  pub async fn init(mut self) -> Result<NeoTrellis<I2C, D>, Error<I2C::Error>> {
+    self.soft_reset().await?;
+    self.setup_neopixel().await?;
+    self.setup_keypad().await?;
+
+    Ok(self.into_state())
+  }
+
+  /// Resets the board and sets up only the keypad, the LEDs stay off.
+  pub async fn init_keypad(mut self) -> Result<NeoTrellis<I2C, D, KeypadOnly>, Error<I2C::Error>> {
+    self.soft_reset().await?;
+    self.setup_keypad().await?;
+
+    Ok(self.into_state())
+  }
+
+  /// Resets the board and sets up only the neopixel module, the keypad reports
+  /// nothing.
+  pub async fn init_neopixel(
+    mut self,
+  ) -> Result<NeoTrellis<I2C, D, NeopixelOnly>, Error<I2C::Error>> {
+    self.soft_reset().await?;
+    self.setup_neopixel().await?;
+
+    Ok(self.into_state())
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: State,
+{
+  /// Resets the seesaw, then polls its hardware ID until the board answers or the
+  /// reset timeout is over.
+  ///
+  /// The board comes back with the LEDs off and the keypad reporting nothing. The
+  /// driver keeps its settings and cached LED colors, `reinit_peripherals` applies
+  /// them to the board again.
+  pub async fn soft_reset(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.soft_reset().await
+  }
+
+  async fn setup_neopixel(&mut self) -> Result<(), Error<I2C::Error>> {
+    let pin = self.neopixel_pin;
+    self
+      .write_register(Module::Neopixel, NEOPIXEL_PIN, &pin.to_be_bytes())
+      .await?;
+
+    let buffer_length = self.pixels.buffer_length();
+    self
+      .write_register(
+        Module::Neopixel,
+        NEOPIXEL_BUF_LENGTH,
+        &buffer_length.to_be_bytes(),
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn setup_keypad(&mut self) -> Result<(), Error<I2C::Error>> {
+    // Enable only the configured events for all 16 keys
+    let commands = keypad_commands(self.key_events, self.disabled_keys);
+
+    self.write_key_commands(&commands).await
+  }
+
+  /// Enables the configured events of the physical `key`, or none if it is disabled.
+  async fn setup_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    let commands = key_commands(key, self.key_events, self.disabled_keys);
+    self.write_key_commands(&commands).await
+  }
+
+  /// Writes `(physical key, command)` pairs, as many as fit into each
+  /// `KEYPAD_EVENT` write.
+  async fn write_key_commands(&mut self, commands: &[(Key, u8)]) -> Result<(), Error<I2C::Error>> {
+    for chunk in commands.chunks(KEY_COMMANDS_PER_WRITE) {
+      let mut value = [0u8; MAX_KEY_COMMAND];
+      let length = encode_key_commands(chunk, &mut value);
+      self
+        .write_register(Module::Keypad, KEYPAD_EVENT, &value[..length])
+        .await?;
+    }
+
+    Ok(())
+  }
+
+  /// Enables or disables `event` for the physical `key`.
+  async fn write_key_event(
+    &mut self,
+    key: Key,
+    event: Event,
+    enable: bool,
+  ) -> Result<(), Error<I2C::Error>> {
+    let command = key_event_command(key, event, enable);
+    self
+      .write_register(Module::Keypad, KEYPAD_EVENT, &command)
+      .await?;
+
+    Ok(())
+  }
+
+  /// Reads a register, retrying as the retry policy says.
+  async fn read_register(
+    &mut self,
+    module: impl Into<u8>,
+    register: u8,
+    value: &mut [u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.read(module.into(), register, value).await
+  }
+
+  /// Writes a register, retrying as the retry policy says.
+  async fn write_register(
+    &mut self,
+    module: impl Into<u8>,
+    register: u8,
+    value: &[u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.write(module.into(), register, value).await
+  }
+
+  /// Reads `value.len()` bytes from `register` of the seesaw `module`, for the
+  /// registers this driver doesn't model. Retried like every other read.
+  pub async fn read_raw(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &mut [u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.read_register(module, register, value).await
+  }
+
+  /// Writes up to 31 bytes to `register` of the seesaw `module`, longer values fail
+  /// with `Error::OutOfBounds`.
+  ///
+  /// The driver doesn't know what the write changes, e.g. writing the pixel buffer
+  /// leaves the cached LED colors out of date.
+  pub async fn write_raw(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &[u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.write_register(module, register, value).await
+  }
+
+  /// Whether the board answers with the seesaw hardware ID, without resetting it.
+  pub(crate) async fn is_present(&mut self) -> bool {
+    self.seesaw.is_present().await
+  }
+
+  /// Reads which seesaw chip the board is built around.
+  pub async fn hardware_id(&mut self) -> Result<HardwareId, Error<I2C::Error>> {
+    self.seesaw.hardware_id().await
+  }
+
+  /// Reads the product ID and build date of the seesaw firmware.
+  pub async fn firmware_version(&mut self) -> Result<FirmwareVersion, Error<I2C::Error>> {
+    self.seesaw.firmware_version().await
+  }
+
+  /// Reads which seesaw modules the firmware implements, for generic seesaw
+  /// boards that may lack the keypad or the neopixel module.
+  pub async fn capabilities(&mut self) -> Result<Capabilities, Error<I2C::Error>> {
+    self.seesaw.capabilities().await
+  }
+
+  /// Reads the temperature of the seesaw chip.
+  pub async fn temperature(&mut self) -> Result<Temperature, Error<I2C::Error>> {
+    self.seesaw.temperature().await
+  }
+
+  /// Gives back the bus and the delay, e.g. to hand the bus to another driver.
+  pub fn free(self) -> (I2C, D) {
+    self.seesaw.free()
+  }
+
+  pub fn address(&self) -> Address {
+    self.seesaw.address()
+  }
+
+  /// Changes the I2C address of the board persistently, see `Seesaw::set_address`.
+  pub async fn set_address(&mut self, address: Address) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.set_address(address).await
+  }
+
+  /// The seesaw transport, e.g. for the modules of custom seesaw firmware this
+  /// driver doesn't use.
+  pub fn seesaw(&mut self) -> &mut Seesaw<I2C, D> {
+    &mut self.seesaw
+  }
+
+  /// Sets how long a reset, e.g. by `recover`, waits for the board to come back
+  /// before failing. The board is polled, so the reset finishes as soon as it
+  /// answers. 500 ms by default.
+  pub fn set_reset_timeout(&mut self, timeout_ms: u32) {
+    self.seesaw.set_reset_timeout(timeout_ms);
+  }
+
+  pub fn reset_timeout(&self) -> u32 {
+    self.seesaw.reset_timeout()
+  }
+
+  /// Sets how failed register reads and writes are retried, by default they aren't.
+  pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+    self.seesaw.set_retry_policy(policy);
+  }
+
+  pub fn retry_policy(&self) -> RetryPolicy {
+    self.seesaw.retry_policy()
+  }
+
+  /// Bus traffic of this board, e.g. to measure what a frame or a poll costs.
+  pub fn stats(&self) -> Stats {
+    self.seesaw.stats()
+  }
+
+  pub fn reset_stats(&mut self) {
+    self.seesaw.reset_stats();
+  }
+
+  /// Events the keypad reports for every key after a setup, see `set_key_events`.
+  pub fn key_events(&self) -> EventSet {
+    self.key_events
+  }
+
+  fn into_state<T>(self) -> NeoTrellis<I2C, D, T> {
+    NeoTrellis {
+      seesaw: self.seesaw,
+      neopixel_pin: self.neopixel_pin,
+      pixels: self.pixels,
+      keymap: self.keymap,
+      speed: self.speed,
+      interrupt: self.interrupt,
+      auto_recover: self.auto_recover,
+      key_events: self.key_events,
+      disabled_keys: self.disabled_keys,
+      state: PhantomData,
+    }
+  }
+
+  /// Resets the board and sets it up again, e.g. after an ESD event locked up
+  /// the seesaw.
+  ///
+  /// Like `reinit_peripherals` after the reset. Without `restore_pixels` the cached
+  /// LED colors are dropped and the LEDs stay off.
+  pub async fn recover(&mut self, restore_pixels: bool) -> Result<(), Error<I2C::Error>> {
+    self.soft_reset().await?;
+    if !restore_pixels {
+      self.pixels.reset();
+    }
+
+    self.reinit_peripherals().await
+  }
+
+  /// Sets up the modules of the state again without a reset, e.g. after a
+  /// brown-out of the LED supply.
+  ///
+  /// The neopixel pin, speed and color order and the keypad interrupt are restored,
+  /// the keypad reports the configured events of all keys again and the
+  /// cached LED colors are shown again.
+  pub async fn reinit_peripherals(&mut self) -> Result<(), Error<I2C::Error>> {
+    if S::NEOPIXEL {
+      self.setup_neopixel().await?;
+      let speed = self.speed;
+      self
+        .write_register(Module::Neopixel, NEOPIXEL_SPEED, &[speed.into()])
+        .await?;
+    }
+    if S::KEYPAD {
+      self.setup_keypad().await?;
+      if self.interrupt {
+        self
+          .write_register(Module::Keypad, KEYPAD_INTENSET, &[0x01])
+          .await?;
+      }
+    }
+
+    if S::NEOPIXEL {
+      self.pixels.mark_dirty();
+      self.show_pixels().await?;
+    }
+
+    Ok(())
+  }
+
+  /// Makes `show_led` and the keypad reads call `recover(true)` first once
+  /// `errors` register accesses failed in a row, `None` turns it off (the default).
+  pub fn set_auto_recover(&mut self, errors: Option<u8>) {
+    self.auto_recover = errors;
+  }
+
+  pub fn auto_recover(&self) -> Option<u8> {
+    self.auto_recover
+  }
+
+  async fn recover_if_needed(&mut self) -> Result<(), Error<I2C::Error>> {
+    match self.auto_recover {
+      Some(errors) if self.seesaw.errors() >= errors => self.recover(true).await,
+      _ => Ok(()),
+    }
+  }
+
+  /// Rotates the board clockwise: LED indices and key events are translated so the
+  /// application keeps working in the orientation the board is looked at.
+  pub fn set_rotation(&mut self, rotation: Rotation) {
+    self.keymap.set_rotation(rotation);
+  }
+
+  pub fn rotation(&self) -> Rotation {
+    self.keymap.rotation()
+  }
+
+  /// Mirrors the key and LED coordinates horizontally and/or vertically, e.g. for a
+  /// board seen from behind a panel. Applied on top of the rotation.
+  pub fn set_mirroring(&mut self, mirror_x: bool, mirror_y: bool) {
+    self.keymap.set_mirroring(mirror_x, mirror_y);
+  }
+
+  pub fn mirroring(&self) -> (bool, bool) {
+    self.keymap.mirroring()
+  }
+
+  /// Installs a remap table where `remap[logical]` is the key a logical key index
+  /// stands for, so custom panel labels are translated once for LEDs and events.
+  ///
+  /// The table is applied before mirroring and rotation. Panics if `remap` is not a
+  /// permutation of the 16 keys.
+  pub fn set_key_remap(&mut self, remap: [u8; 16]) {
+    self.keymap.set_remap(remap);
+  }
+
+  pub fn key_remap(&self) -> &[u8; 16] {
+    self.keymap.remap()
+  }
+
+  async fn write_pixels(
+    &mut self,
+    first_led: u8,
+    colors: &[Color],
+  ) -> Result<(), Error<I2C::Error>> {
+    for (command, length) in pixel_writes(first_led, colors, self.pixels.color_order()) {
+      self
+        .write_register(Module::Neopixel, NEOPIXEL_BUF, &command[..length])
+        .await?;
+
+      self.seesaw.delay().delay_us(100).await;
+    }
+
+    Ok(())
+  }
+
+  async fn show_pixels(&mut self) -> Result<(), Error<I2C::Error>> {
+    let colors = self.pixels.output_colors();
+    while let Some((start, end)) = self.pixels.next_dirty_run() {
+      self
+        .write_pixels(start, &colors[usize::from(start)..usize::from(end)])
+        .await?;
+      self.pixels.mark_clean(start, end);
+    }
+
+    self
+      .write_register(Module::Neopixel, NEOPIXEL_SHOW, &[])
+      .await?;
+
+    self.seesaw.delay().delay_us(100).await;
+
+    Ok(())
+  }
+}
+
+impl<I2C, D> NeoTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Resets and configures the board at `address`.
+  ///
+  /// The driver keeps `delay` for the waits the seesaw protocol requires, so none
+  /// of the other methods need a delay argument.
+  pub async fn new(bus: I2C, address: Address, delay: D) -> Result<Self, Error<I2C::Error>> {
+    NeoTrellis::uninitialized(bus, address, delay).init().await
+  }
+
+  /// Sets up the board as `config` says, see `Config`.
+  pub async fn with_config(bus: I2C, delay: D, config: Config) -> Result<Self, Error<I2C::Error>> {
+    let mut neotrellis = NeoTrellis::uninitialized(bus, config.address, delay);
+    neotrellis.neopixel_pin = config.neopixel_pin;
+    neotrellis.pixels.set_color_order(config.color_order);
+    neotrellis.pixels.set_brightness(config.brightness);
+    neotrellis.key_events = config.key_events;
+    neotrellis.seesaw.set_reset_timeout(config.reset_timeout_ms);
+
+    if config.reset {
+      neotrellis.soft_reset().await?;
+    }
+    neotrellis.setup_neopixel().await?;
+    neotrellis.setup_keypad().await?;
+
+    Ok(neotrellis.into_state())
+  }
+
+  /// Creates the driver without any bus traffic, for a board that was set up
+  /// before, e.g. on a warm restart, skipping the reset and the wait for the board
+  /// to boot.
+  ///
+  /// The board is assumed to be configured as `new` leaves it, the cached LEDs
+  /// start black.
+  pub fn new_unchecked(bus: I2C, address: Address, delay: D) -> Self {
+    NeoTrellis::uninitialized(bus, address, delay).into_state()
+  }
+
+  /// Checks whether a seesaw answers at `address` with its hardware ID, without
+  /// resetting it or taking ownership of the bus, e.g. to find out which optional
+  /// panels are fitted before creating their drivers.
+  ///
+  /// A board that doesn't acknowledge its address gives `Ok(false)`.
+  pub async fn probe(
+    bus: &mut I2C,
+    address: Address,
+    delay: &mut D,
+  ) -> Result<bool, Error<I2C::Error>> {
+    Seesaw::probe(bus, address, delay).await
+  }
+
+  /// Probes every address the address jumpers can select, `NEOTRELLIS_ADDRESSES`,
+  /// returning the ones a seesaw answers at.
+  pub async fn scan(bus: &mut I2C, delay: &mut D) -> Result<Vec<Address, 16>, Error<I2C::Error>> {
+    Self::scan_addresses(bus, delay, NEOTRELLIS_ADDRESSES).await
+  }
+
+  /// Probes the addresses in `addresses`, returning the first 16 a seesaw answers
+  /// at.
+  pub async fn scan_addresses(
+    bus: &mut I2C,
+    delay: &mut D,
+    addresses: RangeInclusive<u8>,
+  ) -> Result<Vec<Address, 16>, Error<I2C::Error>> {
+    let mut found = Vec::new();
+    for address in addresses {
+      let address = Address::new(address);
+      if Self::probe(bus, address, delay).await? && found.push(address).is_err() {
+        break;
+      }
+    }
+
+    Ok(found)
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasNeopixel,
+{
+  /// Scales every color sent to the board by `brightness / 255`.
+  ///
+  /// The new brightness is applied to all LEDs by the next `show_led`.
+  pub fn set_brightness(&mut self, brightness: u8) {
+    self.pixels.set_brightness(brightness);
+  }
+
+  pub fn brightness(&self) -> u8 {
+    self.pixels.brightness()
+  }
+
+  /// Selects the seesaw pin driving the pixels, the NeoTrellis board uses pin 3.
+  ///
+  /// Only needed for other seesaw boards or custom firmware.
+  pub async fn set_neopixel_pin(&mut self, pin: u8) -> Result<(), Error<I2C::Error>> {
+    self.neopixel_pin = pin;
+    self
+      .write_register(Module::Neopixel, NEOPIXEL_PIN, &pin.to_be_bytes())
+      .await
+  }
+
+  pub fn neopixel_pin(&self) -> u8 {
+    self.neopixel_pin
+  }
+
+  /// Sets the data rate of the pixels, the NeoTrellis LEDs run at 800 kHz.
+  pub async fn set_neopixel_speed(&mut self, speed: Speed) -> Result<(), Error<I2C::Error>> {
+    self.speed = speed;
+    self
+      .write_register(Module::Neopixel, NEOPIXEL_SPEED, &[speed.into()])
+      .await
+  }
+
+  /// Selects the pixel layout of the attached LEDs, e.g. `ColorOrder::Grbw` for
+  /// RGBW strips, and resizes the seesaw pixel buffer accordingly.
+  pub async fn set_color_order(&mut self, order: ColorOrder) -> Result<(), Error<I2C::Error>> {
+    self.pixels.set_color_order(order);
+
+    let buffer_length = self.pixels.buffer_length();
+    self
+      .write_register(
+        Module::Neopixel,
+        NEOPIXEL_BUF_LENGTH,
+        &buffer_length.to_be_bytes(),
+      )
+      .await
+  }
+
+  pub fn color_order(&self) -> ColorOrder {
+    self.pixels.color_order()
+  }
+
+  /// The settings `save_settings` stores.
+  pub fn settings(&self) -> Settings {
+    Settings {
+      brightness: self.brightness(),
+      rotation: self.rotation(),
+      color_order: self.color_order(),
+    }
+  }
+
+  pub async fn apply_settings(&mut self, settings: Settings) -> Result<(), Error<I2C::Error>> {
+    self.set_brightness(settings.brightness);
+    self.set_rotation(settings.rotation);
+    self.set_color_order(settings.color_order).await
+  }
+
+  /// Stores the brightness, rotation and color order in the seesaw EEPROM, so
+  /// `load_settings` can restore them after a power cycle.
+  pub async fn save_settings(&mut self) -> Result<(), Error<I2C::Error>> {
+    let blob = self.settings().encode();
+    self.seesaw.eeprom_write(SETTINGS_OFFSET, &blob).await
+  }
+
+  /// Applies the settings stored by `save_settings`, returning them. `None` if the
+  /// EEPROM holds none, e.g. on a new board, the current settings are kept then.
+  pub async fn load_settings(&mut self) -> Result<Option<Settings>, Error<I2C::Error>> {
+    let mut blob = [0u8; SETTINGS_LENGTH];
+    self.seesaw.eeprom_read(SETTINGS_OFFSET, &mut blob).await?;
+
+    let settings = Settings::decode(&blob);
+    if let Some(settings) = settings {
+      self.apply_settings(settings).await?;
+    }
+
+    Ok(settings)
+  }
+
+  /// Enables the gamma-8 correction of every color sent to the board, so low
+  /// intensities look less washed out. Disabled by default.
+  pub fn set_gamma_correction(&mut self, enable: bool) {
+    self.pixels.set_gamma_correction(enable);
+  }
+
+  pub fn gamma_correction(&self) -> bool {
+    self.pixels.gamma_correction()
+  }
+
+  /// Scales the color of a single LED by `brightness / 255`, on top of the global
+  /// brightness. Applied to the LED by the next `show_led`.
+  ///
+  /// Fails with `Error::OutOfBounds` if `led` is not one of the 16 LEDs.
+  pub fn set_pixel_brightness(&mut self, led: u8, brightness: u8) -> Result<(), Error<I2C::Error>> {
+    if led >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    self
+      .pixels
+      .set_pixel_brightness(self.keymap.physical(led), brightness);
+
+    Ok(())
+  }
+
+  pub fn pixel_brightness(&self, led: u8) -> Option<u8> {
+    self.pixels.pixel_brightness(self.keymap.physical(led))
+  }
+
+  /// Sets the color of one LED, it is sent to the board by the next `show_led`.
+  ///
+  /// Fails with `Error::OutOfBounds` if `led` is not one of the 16 LEDs.
+  pub fn set_led_color(&mut self, led: u8, color: Color) -> Result<(), Error<I2C::Error>> {
+    if led >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    self.pixels.set(self.keymap.physical(led), color);
+
+    Ok(())
+  }
+
+  /// Sets the color of all 16 LEDs, they are sent to the board by the next `show_led`.
+  pub fn set_led_colors<C>(&mut self, colors: &[C; 16]) -> Result<(), Error<I2C::Error>>
+  where
+    C: Copy + Into<Color>,
+  {
+    for (led, color) in colors.iter().enumerate() {
+      self
+        .pixels
+        .set(self.keymap.physical(led as u8), (*color).into());
+    }
+
+    Ok(())
+  }
+
+  /// Sets all 16 LEDs to `color`, sent in one bulk write by the next `show_led`.
+  pub fn fill(&mut self, color: Color) {
+    for led in 0..16 {
+      self.pixels.set(led, color);
+    }
+  }
+
+  /// Turns all LEDs off with the next `show_led`.
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Copies the framebuffer to the board and shows it.
+  pub async fn flush(&mut self, framebuffer: &Framebuffer) -> Result<(), Error<I2C::Error>> {
+    self.set_led_colors(framebuffer.board(0, 0))?;
+    self.show_led().await
+  }
+
+  /// Sends the LEDs changed since the last call to the board and shows them.
+  pub async fn show_led(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.recover_if_needed().await?;
+    self.show_pixels().await
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  pub async fn set_key_event(
+    &mut self,
+    key: Key,
+    event: Event,
+    enable: bool,
+  ) -> Result<(), Error<I2C::Error>> {
+    let key = self.keymap.physical_key(key);
+    self.write_key_event(key, event, enable).await
+  }
+
+  /// Makes every key report exactly `events`, e.g. only `Event::Falling` for a
+  /// simple button panel. Unlike `set_key_event` this is kept when the keypad is set
+  /// up again, e.g. by `recover`.
+  pub async fn set_key_events(&mut self, events: EventSet) -> Result<(), Error<I2C::Error>> {
+    self.key_events = events;
+    self.setup_keypad().await
+  }
+
+  /// Enables (or disables) the events in each `(key, events, enable)` entry, packing
+  /// up to 15 keys into each bus write instead of one write per key and event.
+  pub async fn configure_keys(
+    &mut self,
+    keys: &[(Key, EventSet, bool)],
+  ) -> Result<(), Error<I2C::Error>> {
+    if keys.iter().any(|(key, _, _)| key.index() >= 16) {
+      return Err(Error::OutOfBounds);
+    }
+
+    for chunk in keys.chunks(KEY_COMMANDS_PER_WRITE) {
+      let commands = configure_commands(&self.keymap, chunk);
+      self.write_key_commands(&commands[..chunk.len()]).await?;
+    }
+
+    Ok(())
+  }
+
+  /// Stops `key` from reporting any event, e.g. a key that is physically absent or
+  /// covered. Kept when the keypad is set up again.
+  pub async fn disable_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    if key.index() >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    let key = self.keymap.physical_key(key);
+    self.disabled_keys |= 1 << key.index();
+
+    self.setup_key(key).await
+  }
+
+  /// Makes a key disabled by `disable_key` report the configured events again.
+  pub async fn enable_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    if key.index() >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    let key = self.keymap.physical_key(key);
+    self.disabled_keys &= !(1 << key.index());
+
+    self.setup_key(key).await
+  }
+
+  pub fn key_enabled(&self, key: Key) -> bool {
+    key.index() < 16 && self.disabled_keys & (1 << self.keymap.physical_key(key).index()) == 0
+  }
+
+  /// Makes the board pull its INT line low while keypad events are waiting in the
+  /// FIFO.
+  ///
+  /// Instead of polling, the application can then watch the INT pin and only call
+  /// `read_key_events` once it goes low, the line is released when the FIFO has
+  /// been drained.
+  pub async fn enable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.interrupt = true;
+    self
+      .write_register(Module::Keypad, KEYPAD_INTENSET, &[0x01])
+      .await
+  }
+
+  pub async fn disable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.interrupt = false;
+    self
+      .write_register(Module::Keypad, KEYPAD_INTENCLR, &[0x01])
+      .await
+  }
+
+  /// Reads up to `events.len()` events from the keypad FIFO, only transferring as
+  /// many bytes as `keypad_count` reports.
+  ///
+  /// The events are stored at the start of `events`, the remaining slots are set to
+  /// `None`. Returns the number of events read, at most 32 per call.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, the events decoded from
+  /// the other bytes of the read are stored in `events` all the same, as the FIFO
+  /// has handed them over already.
+  pub async fn read_key_events(
+    &mut self,
+    events: &mut [Option<KeypadEvent>],
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut buffer = [0u8; 32];
+    let length = events.len().min(buffer.len());
+    let length = self.read_fifo(&mut buffer[..length]).await?;
+
+    let mut count = 0;
+    let result = decode_fifo(
+      self.seesaw.address().value(),
+      &self.keymap,
+      &buffer[..length],
+      |event| {
+        events[count] = Some(event);
+        count += 1;
+      },
+    );
+    for event in events[count..].iter_mut() {
+      *event = None;
+    }
+
+    result.map(|()| count)
+  }
+
+  /// Suspends until the board pulls `int_pin` low, then reads the pending events
+  /// into `events`.
+  ///
+  /// The keypad interrupt must be enabled, see `enable_interrupt`. Returns the number
+  /// of events read. Combine with a timer future to wait with a timeout.
+  pub async fn wait_for_event<P: Wait>(
+    &mut self,
+    int_pin: &mut P,
+    events: &mut [Option<KeypadEvent>],
+  ) -> Result<usize, Error<I2C::Error>> {
+    int_pin
+      .wait_for_low()
+      .await
+      .map_err(|e| Error::PinError(e.kind()))?;
+
+    self.read_key_events(events).await
+  }
+
+  /// Reads the events currently waiting in the keypad FIFO one at a time, without
+  /// any scratch array:
+  ///
+  /// ```ignore
+  /// let mut events = trellis.events();
+  /// while let Some(event) = events.next().await {
+  ///   let event = event?;
+  ///   // ...
+  /// }
+  /// ```
+  pub fn events(&mut self) -> EventReader<'_, I2C, D, S> {
+    EventReader::new(self)
+  }
+
+  /// Returns the next keypad event, or `None` when the FIFO is empty.
+  ///
+  /// Lets a task check for events between other work instead of waiting for them.
+  pub async fn poll_event(&mut self) -> Result<Option<KeypadEvent>, Error<I2C::Error>> {
+    if self.keypad_count().await? == 0 {
+      return Ok(None);
+    }
+
+    let mut value = [0u8];
+    self
+      .read_register(Module::Keypad, KEYPAD_FIFO, &mut value)
+      .await?;
+
+    decode_event(self.seesaw.address().value(), &self.keymap, value[0])
+  }
+
+  /// Reads the FIFO until it is empty, calling `handler` with every event. Returns
+  /// the number of events handled.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, after the other events
+  /// of the same read were handled.
+  pub async fn drain_events<F: FnMut(KeypadEvent)>(
+    &mut self,
+    mut handler: F,
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut buffer = [0u8; 32];
+    let mut handled = 0;
+
+    loop {
+      let length = self.read_fifo(&mut buffer).await?;
+      if length == 0 {
+        return Ok(handled);
+      }
+
+      let before = handled;
+      decode_fifo(
+        self.seesaw.address().value(),
+        &self.keymap,
+        &buffer[..length],
+        |event| {
+          handler(event);
+          handled += 1;
+        },
+      )?;
+
+      // Don't spin forever on a count that doesn't match the FIFO contents
+      if handled == before {
+        return Ok(handled);
+      }
+    }
+  }
+
+  /// Moves pending events from the keypad FIFO into `queue`, returning how many were
+  /// queued.
+  ///
+  /// Only as many events as `queue` has room for are read, the others stay in the
+  /// FIFO for the next call.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, after the other events
+  /// of the same read were queued.
+  pub async fn pump<const N: usize>(
+    &mut self,
+    queue: &mut EventProducer<'_, N>,
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut buffer = [0u8; 32];
+    let mut queued = 0;
+
+    loop {
+      let room = (queue.capacity() - queue.len()).min(buffer.len());
+      if room == 0 {
+        return Ok(queued);
+      }
+
+      let length = self.read_fifo(&mut buffer[..room]).await?;
+      let before = queued;
+      decode_fifo(
+        self.seesaw.address().value(),
+        &self.keymap,
+        &buffer[..length],
+        |event| {
+          // Can't fail, at most `room` events were read
+          let _ = queue.enqueue(event);
+          queued += 1;
+        },
+      )?;
+
+      if queued == before {
+        return Ok(queued);
+      }
+    }
+  }
+
+  /// Reads as many pending events as `keypad_count` reports and `buffer` can hold,
+  /// returning the number of bytes read.
+  async fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<usize, Error<I2C::Error>> {
+    let count = usize::from(self.keypad_count().await?).min(buffer.len());
+    if count > 0 {
+      self
+        .read_register(Module::Keypad, KEYPAD_FIFO, &mut buffer[..count])
+        .await?;
+    }
+
+    Ok(count)
+  }
+
+  pub async fn keypad_count(&mut self) -> Result<u8, Error<I2C::Error>> {
+    self.recover_if_needed().await?;
+    let mut value = [0u8];
+    self
+      .read_register(Module::Keypad, KEYPAD_COUNT, &mut value)
+      .await?;
+
+    let count = u8::from_be_bytes(value);
+
+    Ok(count)
+  }
+}
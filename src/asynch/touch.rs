@@ -0,0 +1,47 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+use super::Seesaw;
+use crate::{Error, Module};
+
+const TOUCH_CHANNEL_OFFSET: u8 = 0x10;
+/// Touch channels of the seesaw firmware, the ATtiny firmware has no touch module.
+const TOUCH_CHANNELS: u8 = 4;
+
+/// Capacitive touch pads of the seesaw touch module, e.g. a few pads next to the
+/// keypad on custom firmware.
+impl<I2C, D> Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Reads the raw capacitance of touch `channel`, which rises when the pad is
+  /// touched. How high depends on the pad, compare it to a threshold measured on
+  /// the finished panel.
+  ///
+  /// Fails with `Error::OutOfBounds` for a channel the touch module doesn't have.
+  pub async fn touch_read(&mut self, channel: u8) -> Result<u16, Error<I2C::Error>> {
+    if channel >= TOUCH_CHANNELS {
+      return Err(Error::OutOfBounds);
+    }
+    let mut value = [0u8; 2];
+    self
+      .read(
+        Module::Touch.into(),
+        TOUCH_CHANNEL_OFFSET + channel,
+        &mut value,
+      )
+      .await?;
+
+    Ok(u16::from_be_bytes(value))
+  }
+
+  /// Whether touch `channel` reads above `threshold`.
+  pub async fn is_touched(
+    &mut self,
+    channel: u8,
+    threshold: u16,
+  ) -> Result<bool, Error<I2C::Error>> {
+    Ok(self.touch_read(channel).await? > threshold)
+  }
+}
@@ -0,0 +1,190 @@
+//! Driver for the original Adafruit Trellis, whose 16 single color LEDs and keys
+//! are driven by an HT16K33 instead of a seesaw.
+//!
+//! Keys are numbered and reported like on a NeoTrellis, so application code that
+//! handles `KeypadEvent`s works with both boards:
+//!
+//! ```ignore
+//! let mut trellis = Trellis::new(i2c, Trellis::DEFAULT_ADDRESS)?;
+//! let mut toggled = 0u16;
+//! trellis.drain_events(|event| {
+//!   if event.event == Event::Rising {
+//!     toggled ^= 1 << event.key.index();
+//!   }
+//! })?;
+//! for led in 0..16 {
+//!   trellis.set_led(led, (toggled >> led) & 1 != 0)?;
+//! }
+//! trellis.show_led()?;
+//! ```
+
+use embedded_hal::i2c::I2c;
+
+use crate::{Address, Error, Event, Key, KeypadEvent};
+
+const OSCILLATOR_ON: u8 = 0x21;
+const DISPLAY_ON: u8 = 0x81;
+const BRIGHTNESS: u8 = 0xE0;
+/// Makes the INT pin an active low interrupt output.
+const INTERRUPT_ACTIVE_LOW: u8 = 0xA1;
+const DISPLAY_RAM: u8 = 0x00;
+const KEY_RAM: u8 = 0x40;
+
+/// Display RAM bit of every LED.
+const LED_BITS: [u8; 16] = [
+  0x3A, 0x37, 0x35, 0x34, 0x28, 0x29, 0x23, 0x24, 0x16, 0x1B, 0x11, 0x10, 0x0E, 0x0D, 0x0C, 0x02,
+];
+/// Key RAM bit of every key, the byte in the high and the bit in the low nibble.
+const KEY_BITS: [u8; 16] = [
+  0x07, 0x04, 0x02, 0x22, 0x05, 0x06, 0x00, 0x01, 0x03, 0x10, 0x30, 0x21, 0x13, 0x12, 0x11, 0x31,
+];
+
+pub struct Trellis<I2C>
+where
+  I2C: I2c,
+{
+  bus: I2C,
+  address: u8,
+  leds: u16,
+  pressed: u16,
+}
+
+impl<I2C> Trellis<I2C>
+where
+  I2C: I2c,
+{
+  /// Address of a board without address jumpers.
+  pub const DEFAULT_ADDRESS: Address = Address::new(0x70);
+
+  /// Starts the HT16K33 at `address` with the LEDs off at full brightness.
+  pub fn new(bus: I2C, address: Address) -> Result<Self, Error<I2C::Error>> {
+    let mut trellis = Self {
+      bus,
+      address: address.value(),
+      leds: 0,
+      pressed: 0,
+    };
+
+    trellis.command(OSCILLATOR_ON)?;
+    trellis.command(INTERRUPT_ACTIVE_LOW)?;
+    trellis.set_brightness(15)?;
+    trellis.show_led()?;
+    trellis.command(DISPLAY_ON)?;
+
+    Ok(trellis)
+  }
+
+  /// The HT16K33 has no register modules, errors report the command as register.
+  fn write(&mut self, bytes: &[u8]) -> Result<(), Error<I2C::Error>> {
+    self
+      .bus
+      .write(self.address, bytes)
+      .map_err(|error| Error::WriteError {
+        address: self.address,
+        module: 0,
+        register: bytes[0],
+        error,
+      })
+  }
+
+  fn command(&mut self, command: u8) -> Result<(), Error<I2C::Error>> {
+    self.write(&[command])
+  }
+
+  /// Sets the brightness of all LEDs, from 0 to 15.
+  pub fn set_brightness(&mut self, brightness: u8) -> Result<(), Error<I2C::Error>> {
+    self.command(BRIGHTNESS | brightness.min(15))
+  }
+
+  /// Turns LED `led` on or off, it is sent to the board by the next `show_led`.
+  pub fn set_led(&mut self, led: u8, on: bool) -> Result<(), Error<I2C::Error>> {
+    if led >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    if on {
+      self.leds |= 1 << led;
+    } else {
+      self.leds &= !(1 << led);
+    }
+
+    Ok(())
+  }
+
+  pub fn led(&self, led: u8) -> bool {
+    led < 16 && self.leds & (1 << led) != 0
+  }
+
+  /// Turns all LEDs off.
+  pub fn clear(&mut self) {
+    self.leds = 0;
+  }
+
+  pub fn show_led(&mut self) -> Result<(), Error<I2C::Error>> {
+    let mut command = [0u8; 17];
+    command[0] = DISPLAY_RAM;
+    for (led, bit) in LED_BITS.iter().enumerate() {
+      if self.leds & (1 << led) != 0 {
+        let row = usize::from(bit >> 4);
+        command[1 + 2 * row + usize::from(bit & 0x0f) / 8] |= 1 << (bit & 0x07);
+      }
+    }
+
+    self.write(&command)
+  }
+
+  /// Reads the keys and calls `handler` with a rising edge for every key pressed
+  /// and a falling edge for every key released since the last call, returning the
+  /// number of events.
+  pub fn drain_events<F: FnMut(KeypadEvent)>(
+    &mut self,
+    mut handler: F,
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut ram = [0u8; 6];
+    self
+      .bus
+      .write_read(self.address, &[KEY_RAM], &mut ram)
+      .map_err(|error| Error::ReadError {
+        address: self.address,
+        module: 0,
+        register: KEY_RAM,
+        error,
+      })?;
+
+    let mut pressed = 0u16;
+    for (key, bit) in KEY_BITS.iter().enumerate() {
+      if ram[usize::from(bit >> 4)] & (1 << (bit & 0x0f)) != 0 {
+        pressed |= 1 << key;
+      }
+    }
+
+    let changed = pressed ^ self.pressed;
+    self.pressed = pressed;
+    for key in (0..16).filter(|key| changed & (1 << key) != 0) {
+      let event = if pressed & (1 << key) != 0 {
+        Event::Rising
+      } else {
+        Event::Falling
+      };
+      handler(KeypadEvent {
+        key: Key::from_index(key),
+        event,
+      });
+    }
+
+    Ok(changed.count_ones() as usize)
+  }
+
+  /// Whether `key` was held down at the last `drain_events`.
+  pub fn is_pressed(&self, key: Key) -> bool {
+    key.index() < 16 && self.pressed & (1 << key.index()) != 0
+  }
+
+  pub fn address(&self) -> Address {
+    Address::new(self.address)
+  }
+
+  /// Gives back the bus.
+  pub fn free(self) -> I2C {
+    self.bus
+  }
+}
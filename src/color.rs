@@ -0,0 +1,228 @@
+/// An RGB color with an optional white channel, which is only sent to RGBW pixels.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+  pub w: u8,
+}
+
+/// Byte order of one pixel in the seesaw neopixel buffer, which depends on the
+/// pixel chip. The `W` variants are 4 bytes RGBW pixels.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorOrder {
+  /// WS2812 style pixels, as on the NeoTrellis board.
+  #[default]
+  Grb,
+  Rgb,
+  Rbg,
+  Gbr,
+  Brg,
+  Bgr,
+  /// SK6812 style RGBW pixels.
+  Grbw,
+  Rgbw,
+}
+
+impl ColorOrder {
+  pub const fn bytes_per_pixel(&self) -> usize {
+    match self {
+      ColorOrder::Grbw | ColorOrder::Rgbw => 4,
+      _ => 3,
+    }
+  }
+
+  /// Writes `color` in wire order into the first `bytes_per_pixel` bytes of `out`.
+  pub(crate) fn encode(&self, color: Color, out: &mut [u8]) {
+    let Color { r, g, b, w } = color;
+    match self {
+      ColorOrder::Grb => out[..3].copy_from_slice(&[g, r, b]),
+      ColorOrder::Rgb => out[..3].copy_from_slice(&[r, g, b]),
+      ColorOrder::Rbg => out[..3].copy_from_slice(&[r, b, g]),
+      ColorOrder::Gbr => out[..3].copy_from_slice(&[g, b, r]),
+      ColorOrder::Brg => out[..3].copy_from_slice(&[b, r, g]),
+      ColorOrder::Bgr => out[..3].copy_from_slice(&[b, g, r]),
+      ColorOrder::Grbw => out[..4].copy_from_slice(&[g, r, b, w]),
+      ColorOrder::Rgbw => out[..4].copy_from_slice(&[r, g, b, w]),
+    }
+  }
+}
+
+impl Color {
+  pub const BLACK: Color = Color::rgb(0, 0, 0);
+  pub const WHITE: Color = Color::rgb(255, 255, 255);
+  pub const RED: Color = Color::rgb(255, 0, 0);
+  pub const GREEN: Color = Color::rgb(0, 255, 0);
+  pub const BLUE: Color = Color::rgb(0, 0, 255);
+  pub const YELLOW: Color = Color::rgb(255, 255, 0);
+  pub const CYAN: Color = Color::rgb(0, 255, 255);
+  pub const MAGENTA: Color = Color::rgb(255, 0, 255);
+  pub const ORANGE: Color = Color::rgb(255, 128, 0);
+  pub const PURPLE: Color = Color::rgb(128, 0, 255);
+
+  pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+    Color { r, g, b, w: 0 }
+  }
+
+  pub const fn rgbw(r: u8, g: u8, b: u8, w: u8) -> Self {
+    Color { r, g, b, w }
+  }
+
+  /// Builds a color from a `0xRRGGBB` value, the upper byte is ignored.
+  pub const fn from_u32(rgb: u32) -> Self {
+    Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+  }
+
+  /// Packs the color as `0xRRGGBB`.
+  pub const fn to_u32(&self) -> u32 {
+    (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+  }
+
+  /// Builds a color from a hue, saturation and value using integer math only.
+  ///
+  /// `hue` covers the whole color wheel: 0 is red, 21845 green, 43690 blue and it
+  /// wraps back to red at 65535.
+  pub const fn hsv(hue: u16, saturation: u8, value: u8) -> Self {
+    // Bring the hue into 0..1530: six ramps of 255 steps between the primaries
+    let hue = (hue as u32 * 1530 + 32768) / 65536;
+    let (r, g, b) = if hue < 255 {
+      (255, hue, 0)
+    } else if hue < 510 {
+      (510 - hue, 255, 0)
+    } else if hue < 765 {
+      (0, 255, hue - 510)
+    } else if hue < 1020 {
+      (0, 1020 - hue, 255)
+    } else if hue < 1275 {
+      (hue - 1020, 0, 255)
+    } else if hue < 1530 {
+      (255, 0, 1530 - hue)
+    } else {
+      (255, 0, 0)
+    };
+
+    let s1 = 1 + saturation as u32;
+    let s2 = 255 - saturation as u32;
+    let v1 = 1 + value as u32;
+
+    Color::rgb(
+      (((((r * s1) >> 8) + s2) * v1) >> 8) as u8,
+      (((((g * s1) >> 8) + s2) * v1) >> 8) as u8,
+      (((((b * s1) >> 8) + s2) * v1) >> 8) as u8,
+    )
+  }
+
+  /// Classic rainbow wheel of the Adafruit examples: `pos` goes from green to red
+  /// to blue and back to green over 0..=255.
+  pub const fn wheel(pos: u8) -> Self {
+    if pos < 85 {
+      Color::rgb(pos * 3, 255 - pos * 3, 0)
+    } else if pos < 170 {
+      let pos = pos - 85;
+      Color::rgb(255 - pos * 3, 0, pos * 3)
+    } else {
+      let pos = pos - 170;
+      Color::rgb(0, pos * 3, 255 - pos * 3)
+    }
+  }
+
+  /// Scales every channel by `factor / 255`, 255 leaving the color unchanged.
+  pub const fn scale(&self, factor: u8) -> Self {
+    Color {
+      r: scale_channel(self.r, factor),
+      g: scale_channel(self.g, factor),
+      b: scale_channel(self.b, factor),
+      w: scale_channel(self.w, factor),
+    }
+  }
+
+  /// Linear interpolation towards `other`: `t = 0` gives `self`, `t = 255` gives
+  /// `other`. Useful for fades and crossfades.
+  pub const fn blend(&self, other: Color, t: u8) -> Self {
+    Color {
+      r: lerp_channel(self.r, other.r, t),
+      g: lerp_channel(self.g, other.g, t),
+      b: lerp_channel(self.b, other.b, t),
+      w: lerp_channel(self.w, other.w, t),
+    }
+  }
+
+  pub const fn as_grb_slice(&self) -> [u8; 3] {
+    [self.g, self.r, self.b]
+  }
+}
+
+const fn scale_channel(value: u8, factor: u8) -> u8 {
+  ((value as u16 * (factor as u16 + 1)) >> 8) as u8
+}
+
+const fn lerp_channel(from: u8, to: u8, t: u8) -> u8 {
+  ((from as u32 * (255 - t as u32) + to as u32 * t as u32 + 127) / 255) as u8
+}
+
+impl Default for Color {
+  fn default() -> Self {
+    Color::BLACK
+  }
+}
+
+impl From<u32> for Color {
+  fn from(rgb: u32) -> Self {
+    Color::from_u32(rgb)
+  }
+}
+
+impl From<Color> for u32 {
+  fn from(color: Color) -> Self {
+    color.to_u32()
+  }
+}
+
+#[cfg(feature = "rgb")]
+impl From<rgb::RGB8> for Color {
+  fn from(color: rgb::RGB8) -> Self {
+    Color::rgb(color.r, color.g, color.b)
+  }
+}
+
+/// Drops the white channel.
+#[cfg(feature = "rgb")]
+impl From<Color> for rgb::RGB8 {
+  fn from(color: Color) -> Self {
+    rgb::RGB8::new(color.r, color.g, color.b)
+  }
+}
+
+#[cfg(feature = "palette")]
+impl From<palette::Srgb<u8>> for Color {
+  fn from(color: palette::Srgb<u8>) -> Self {
+    Color::rgb(color.red, color.green, color.blue)
+  }
+}
+
+#[cfg(feature = "palette")]
+impl From<palette::Srgb> for Color {
+  fn from(color: palette::Srgb) -> Self {
+    color.into_format::<u8>().into()
+  }
+}
+
+/// Converts to sRGB first, so blends and gradients computed in linear space show
+/// the intended intensities.
+#[cfg(feature = "palette")]
+impl From<palette::LinSrgb> for Color {
+  fn from(color: palette::LinSrgb) -> Self {
+    palette::Srgb::<u8>::from_linear(color).into()
+  }
+}
+
+#[cfg(feature = "palette")]
+impl From<palette::Hsv> for Color {
+  fn from(color: palette::Hsv) -> Self {
+    use palette::FromColor;
+
+    palette::Srgb::<f32>::from_color(color).into()
+  }
+}
@@ -0,0 +1,69 @@
+use crate::{Address, ColorOrder, Event, NEOTRELLIS_NEOPIXEL_PIN};
+
+/// A set of keypad events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventSet(u8);
+
+impl EventSet {
+  pub const NONE: Self = Self(0);
+  pub const ALL: Self = Self(0x0f);
+  /// Key presses and releases.
+  pub const EDGES: Self = Self::NONE.with(Event::Rising).with(Event::Falling);
+  /// Key held and key not held, reported at every keypad scan.
+  pub const LEVELS: Self = Self::NONE.with(Event::High).with(Event::Low);
+
+  pub const fn with(self, event: Event) -> Self {
+    Self(self.0 | 1 << event as u8)
+  }
+
+  pub const fn without(self, event: Event) -> Self {
+    Self(self.0 & !(1 << event as u8))
+  }
+
+  pub const fn contains(self, event: Event) -> bool {
+    self.0 & 1 << event as u8 != 0
+  }
+
+  /// One bit per event, `1 << event`.
+  pub const fn bits(self) -> u8 {
+    self.0
+  }
+}
+
+/// How `NeoTrellis::with_config` sets up a board.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+  pub address: Address,
+  /// Seesaw pin wired to the pixels.
+  pub neopixel_pin: u8,
+  pub color_order: ColorOrder,
+  pub brightness: u8,
+  /// Events the keypad reports for every key.
+  pub key_events: EventSet,
+  /// Without a reset the board is set up as it is, e.g. on a warm restart.
+  pub reset: bool,
+  /// How long a reset waits for the board to come back, see
+  /// `NeoTrellis::set_reset_timeout`.
+  pub reset_timeout_ms: u32,
+}
+
+impl Config {
+  /// The configuration `NeoTrellis::new` uses for the board at `address`.
+  pub const fn new(address: Address) -> Self {
+    Self {
+      address,
+      neopixel_pin: NEOTRELLIS_NEOPIXEL_PIN,
+      color_order: ColorOrder::Grb,
+      brightness: 255,
+      key_events: EventSet::EDGES,
+      reset: true,
+      reset_timeout_ms: 500,
+    }
+  }
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self::new(Address::DEFAULT)
+  }
+}
@@ -0,0 +1,87 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::{Color, Error, MultiEvent, NeoTrellis, Placement, TrellisGrid};
+
+/// The LEDs and keys of a grid, whether it's one board, a `MultiTrellis` or a
+/// simulator, so application code can be written once and run off-target.
+///
+/// Positions are `(x, y)` grid coordinates, key events are `MultiEvent`s.
+pub trait TrellisDevice {
+  type Error;
+
+  /// Width and height of the grid, in keys.
+  fn size(&self) -> (u8, u8);
+
+  /// Sets the color of the LED at `index`, applied by the next `show`. Fails with
+  /// `Error::OutOfBounds` outside of the grid.
+  fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), Self::Error>;
+
+  /// Sets every LED to `color`, applied by the next `show`.
+  fn fill(&mut self, color: Color);
+
+  fn show(&mut self) -> Result<(), Self::Error>;
+
+  /// Passes every pending key event to `handler`, returning the number of events.
+  fn drain_events<F: FnMut(MultiEvent)>(&mut self, handler: F) -> Result<usize, Self::Error>;
+}
+
+impl<I2C, D> TrellisDevice for NeoTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  type Error = Error<I2C::Error>;
+
+  fn size(&self) -> (u8, u8) {
+    (4, 4)
+  }
+
+  fn set_led_color(&mut self, (x, y): (u8, u8), color: Color) -> Result<(), Self::Error> {
+    if x >= 4 || y >= 4 {
+      return Err(Error::OutOfBounds);
+    }
+    NeoTrellis::set_led_color(self, x + 4 * y, color)
+  }
+
+  fn fill(&mut self, color: Color) {
+    NeoTrellis::fill(self, color);
+  }
+
+  fn show(&mut self) -> Result<(), Self::Error> {
+    self.show_led()
+  }
+
+  fn drain_events<F: FnMut(MultiEvent)>(&mut self, mut handler: F) -> Result<usize, Self::Error> {
+    NeoTrellis::drain_events(self, |event| {
+      handler(MultiEvent::from_placement(Placement::new(0, 0), event))
+    })
+  }
+}
+
+impl<T, E, const W: usize, const H: usize> TrellisDevice for TrellisGrid<T, W, H>
+where
+  T: TrellisDevice<Error = Error<E>>,
+{
+  type Error = Error<E>;
+
+  fn size(&self) -> (u8, u8) {
+    self.layout().size()
+  }
+
+  fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), Self::Error> {
+    TrellisGrid::set_led_color(self, index, color)
+  }
+
+  fn fill(&mut self, color: Color) {
+    TrellisGrid::fill(self, color);
+  }
+
+  fn show(&mut self) -> Result<(), Self::Error> {
+    TrellisGrid::show(self)
+  }
+
+  fn drain_events<F: FnMut(MultiEvent)>(&mut self, handler: F) -> Result<usize, Self::Error> {
+    TrellisGrid::drain_events(self, handler)
+  }
+}
@@ -0,0 +1,59 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::Seesaw;
+use crate::{Address, Error, HardwareId, Module};
+
+/// Bytes of EEPROM written by each bus write.
+const EEPROM_CHUNK: usize = 30;
+
+/// Wait for the chip to take over a new address.
+const ADDRESS_CHANGE_MS: u32 = 250;
+
+/// The EEPROM of the seesaw chip, which keeps its content across power cycles.
+///
+/// Its last byte holds the I2C address the chip answers at, `set_address` changes
+/// it.
+impl<I2C, D> Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Reads `buffer.len()` bytes of EEPROM starting at `offset`.
+  pub fn eeprom_read(&mut self, offset: u8, buffer: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+    self.read(Module::Eeprom.into(), offset, buffer)
+  }
+
+  /// Writes `data` to the EEPROM starting at `offset`.
+  ///
+  /// Writing the last byte, see `eeprom_address_offset`, changes the I2C address
+  /// of the chip.
+  pub fn eeprom_write(&mut self, offset: u8, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+    for (i, chunk) in data.chunks(EEPROM_CHUNK).enumerate() {
+      let offset = offset.wrapping_add((i * EEPROM_CHUNK) as u8);
+      self.write(Module::Eeprom.into(), offset, chunk)?;
+    }
+
+    Ok(())
+  }
+
+  /// EEPROM offset of the I2C address, the last byte of the EEPROM of the chip.
+  pub fn eeprom_address_offset(&mut self) -> Result<u8, Error<I2C::Error>> {
+    Ok(match self.hardware_id()? {
+      HardwareId::Samd09 => 0x3F,
+      _ => 0x7F,
+    })
+  }
+
+  /// Changes the I2C address of the chip persistently, e.g. to chain boards
+  /// without soldering their address jumpers, and talks to it at `address` from
+  /// then on.
+  pub fn set_address(&mut self, address: Address) -> Result<(), Error<I2C::Error>> {
+    let offset = self.eeprom_address_offset()?;
+    self.eeprom_write(offset, &[address.value()])?;
+    self.delay().delay_ms(ADDRESS_CHANGE_MS);
+    self.address = address.value();
+
+    Ok(())
+  }
+}
@@ -1,27 +1,219 @@
-use embedded_hal::blocking::i2c::{Write, Read};
-use core::fmt::Formatter;
-
-pub enum Error<I2C> 
-    where I2C: Read + Write,
-          <I2C as Read>::Error: core::fmt::Debug,
-          <I2C as Write>::Error: core::fmt::Debug
-{
-    WriteError(<I2C as Write>::Error),
-    ReadError(<I2C as Read>::Error),
-    WrongChipId,
-}
-
-impl<I2c> core::fmt::Debug for Error<I2c>
-    where
-        I2c: Read + Write,
-        <I2c as Read>::Error: core::fmt::Debug,
-        <I2c as Write>::Error: core::fmt::Debug,
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::result::Result<(), core::fmt::Error> {
+use core::fmt;
+
+use embedded_hal::digital::ErrorKind;
+
+/// Errors returned by the driver, generic over the I2C bus error type.
+///
+/// Errors caused by a board carry its I2C address, so the failing board of a
+/// `MultiTrellis` can be told apart. Bus errors also carry the seesaw module and
+/// register that were accessed.
+#[derive(Debug)]
+pub enum Error<E> {
+    WriteError {
+        address: u8,
+        module: u8,
+        register: u8,
+        error: E,
+    },
+    ReadError {
+        address: u8,
+        module: u8,
+        register: u8,
+        error: E,
+    },
+    /// The board at `address` reported the hardware ID `id`, which is not a known
+    /// seesaw chip, see `HardwareId`.
+    WrongChipId { address: u8, id: u8 },
+    /// Reading the interrupt pin failed.
+    PinError(ErrorKind),
+    /// An LED index or grid coordinate outside of the board or grid.
+    OutOfBounds,
+    /// The board sent data that makes no sense, e.g. a keypad FIFO byte naming a
+    /// key that doesn't exist.
+    ProtocolError { address: u8, byte: u8 },
+    /// The board of a `SharedTrellis` is in use by its other half, e.g. an interrupt
+    /// handler fired during a transfer of the main loop. Nothing was sent.
+    Busy,
+}
+
+impl<E> Error<E> {
+    /// I2C address of the board that caused the error.
+    pub fn address(&self) -> Option<u8> {
+        match self {
+            Error::WriteError { address, .. }
+            | Error::ReadError { address, .. }
+            | Error::WrongChipId { address, .. }
+            | Error::ProtocolError { address, .. } => Some(*address),
+            Error::PinError(_) | Error::OutOfBounds | Error::Busy => None,
+        }
+    }
+}
+
+impl<E: embedded_hal::i2c::Error> Error<E> {
+    /// Kind of the bus error, e.g. `ErrorKind::NoAcknowledge` for a board that is
+    /// missing or at another address. `None` for errors that didn't come from the bus.
+    pub fn kind(&self) -> Option<embedded_hal::i2c::ErrorKind> {
+        match self {
+            Error::WriteError { error, .. } | Error::ReadError { error, .. } => Some(error.kind()),
+            _ => None,
+        }
+    }
+}
+
+/// The bus error itself is left to `source`.
+impl<E> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::WriteError {
+                address,
+                module,
+                register,
+                ..
+            } => write!(
+                f,
+                "write error at {:#x} (module {:#x}, register {:#x})",
+                address, module, register
+            ),
+            Error::ReadError {
+                address,
+                module,
+                register,
+                ..
+            } => write!(
+                f,
+                "read error at {:#x} (module {:#x}, register {:#x})",
+                address, module, register
+            ),
+            Error::WrongChipId { address, id } => {
+                write!(f, "unknown chip id {:#x} at {:#x}", id, address)
+            }
+            Error::PinError(kind) => write!(f, "interrupt pin error: {}", kind),
+            Error::OutOfBounds => f.write_str("out of bounds"),
+            Error::Busy => f.write_str("busy"),
+            Error::ProtocolError { address, byte } => {
+                write!(f, "unexpected byte {:#x} from {:#x}", byte, address)
+            }
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
-            Error::ReadError(e) => f.debug_tuple("ReadError").field(e).finish(),
-            Error::WriteError(e) => f.debug_tuple("WriteError").field(e).finish(),
-            Error::WrongChipId => f.debug_tuple("WrongChipId").finish(),
+            Error::WriteError { error, .. } | Error::ReadError { error, .. } => Some(error),
+            _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Bus errors are logged as their `embedded_hal::i2c::ErrorKind`, as few HAL error
+/// types implement `defmt::Format`.
+#[cfg(feature = "defmt")]
+impl<E: embedded_hal::i2c::Error> defmt::Format for Error<E> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Error::WriteError {
+                address,
+                module,
+                register,
+                error,
+            } => defmt::write!(
+                f,
+                "WriteError {{ address: {:#x}, module: {:#x}, register: {:#x}, error: {} }}",
+                address,
+                module,
+                register,
+                error.kind()
+            ),
+            Error::ReadError {
+                address,
+                module,
+                register,
+                error,
+            } => defmt::write!(
+                f,
+                "ReadError {{ address: {:#x}, module: {:#x}, register: {:#x}, error: {} }}",
+                address,
+                module,
+                register,
+                error.kind()
+            ),
+            Error::WrongChipId { address, id } => defmt::write!(
+                f,
+                "WrongChipId {{ address: {=u8:#x}, id: {=u8:#x} }}",
+                address,
+                id
+            ),
+            Error::PinError(kind) => defmt::write!(f, "PinError({})", kind),
+            Error::OutOfBounds => defmt::write!(f, "OutOfBounds"),
+            Error::Busy => defmt::write!(f, "Busy"),
+            Error::ProtocolError { address, byte } => defmt::write!(
+                f,
+                "ProtocolError {{ address: {=u8:#x}, byte: {=u8:#x} }}",
+                address,
+                byte
+            ),
+        }
+    }
+}
+
+/// Bus errors are printed with their `embedded_hal::i2c::ErrorKind`, e.g.
+/// `read error at 0x2e (module 0x10, register 0x10): no acknowledge`.
+#[cfg(feature = "ufmt")]
+impl<E: embedded_hal::i2c::Error> ufmt::uDisplay for Error<E> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        match self {
+            Error::WriteError {
+                address,
+                module,
+                register,
+                error,
+            } => ufmt::uwrite!(
+                f,
+                "write error at {:#x} (module {:#x}, register {:#x}): {}",
+                *address,
+                *module,
+                *register,
+                i2c_error_name(error.kind())
+            ),
+            Error::ReadError {
+                address,
+                module,
+                register,
+                error,
+            } => ufmt::uwrite!(
+                f,
+                "read error at {:#x} (module {:#x}, register {:#x}): {}",
+                *address,
+                *module,
+                *register,
+                i2c_error_name(error.kind())
+            ),
+            Error::WrongChipId { address, id } => {
+                ufmt::uwrite!(f, "unknown chip id {:#x} at {:#x}", *id, *address)
+            }
+            Error::PinError(_) => f.write_str("interrupt pin error"),
+            Error::OutOfBounds => f.write_str("out of bounds"),
+            Error::Busy => f.write_str("busy"),
+            Error::ProtocolError { address, byte } => {
+                ufmt::uwrite!(f, "unexpected byte {:#x} from {:#x}", *byte, *address)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+fn i2c_error_name(kind: embedded_hal::i2c::ErrorKind) -> &'static str {
+    use embedded_hal::i2c::ErrorKind;
+
+    match kind {
+        ErrorKind::Bus => "bus error",
+        ErrorKind::ArbitrationLoss => "arbitration loss",
+        ErrorKind::NoAcknowledge(_) => "no acknowledge",
+        ErrorKind::Overrun => "overrun",
+        _ => "other",
+    }
+}
@@ -0,0 +1,73 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use heapless::spsc::{Consumer, Producer, Queue};
+
+use crate::keypad::FifoBuffer;
+use crate::{Error, HasKeypad, KeypadEvent, NeoTrellis, Ready};
+
+/// Single producer single consumer queue of keypad events, filled by
+/// `NeoTrellis::pump` (e.g. from an interrupt handler) and emptied by the main loop.
+///
+/// Like every `heapless::spsc::Queue` it holds at most `N - 1` events.
+pub type EventQueue<const N: usize> = Queue<KeypadEvent, N>;
+pub type EventProducer<'a, const N: usize> = Producer<'a, KeypadEvent, N>;
+pub type EventConsumer<'a, const N: usize> = Consumer<'a, KeypadEvent, N>;
+
+/// Iterator over the events pending in the keypad FIFO, see `NeoTrellis::events`.
+///
+/// The FIFO is read on the first call to `next`, the events are then decoded one at
+/// a time. A bus or protocol error is returned once and ends the iteration.
+pub struct EventReader<'a, I2C, D, S = Ready>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  trellis: &'a mut NeoTrellis<I2C, D, S>,
+  buffer: FifoBuffer,
+  fetched: bool,
+}
+
+impl<'a, I2C, D, S> EventReader<'a, I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  pub(crate) fn new(trellis: &'a mut NeoTrellis<I2C, D, S>) -> Self {
+    Self {
+      trellis,
+      buffer: FifoBuffer::new(),
+      fetched: false,
+    }
+  }
+
+  fn fetch(&mut self) -> Result<(), Error<I2C::Error>> {
+    let length = self.trellis.read_fifo(self.buffer.bytes_mut())?;
+    self.buffer.set_length(length);
+
+    Ok(())
+  }
+}
+
+impl<'a, I2C, D, S> Iterator for EventReader<'a, I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  type Item = Result<KeypadEvent, Error<I2C::Error>>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if !self.fetched {
+      self.fetched = true;
+      if let Err(e) = self.fetch() {
+        return Some(Err(e));
+      }
+    }
+
+    self
+      .buffer
+      .next_event(self.trellis.seesaw.address().value(), &self.trellis.keymap)
+  }
+}
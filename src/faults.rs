@@ -0,0 +1,83 @@
+use crate::Error;
+
+/// Health of one board of a `MultiTrellis`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardStatus {
+  Ok,
+  /// The last bus access of the board failed.
+  Faulted,
+}
+
+/// Tracks which boards of a `MultiTrellis` failed.
+///
+/// A board is faulted when an operation on it fails and is healthy again after a
+/// successful one. In fault tolerant mode errors of a board are not returned and
+/// faulted boards are skipped until the faults are cleared.
+pub(crate) struct Faults<const W: usize, const H: usize> {
+  tolerant: bool,
+  faulted: [[bool; H]; W],
+}
+
+impl<const W: usize, const H: usize> Faults<W, H> {
+  pub(crate) const fn new() -> Self {
+    Self {
+      tolerant: false,
+      faulted: [[false; H]; W],
+    }
+  }
+
+  pub(crate) fn tolerant(&self) -> bool {
+    self.tolerant
+  }
+
+  pub(crate) fn set_tolerant(&mut self, tolerant: bool) {
+    self.tolerant = tolerant;
+  }
+
+  pub(crate) fn status(&self, x: usize, y: usize) -> BoardStatus {
+    if self.faulted[x][y] {
+      BoardStatus::Faulted
+    } else {
+      BoardStatus::Ok
+    }
+  }
+
+  pub(crate) fn set(&mut self, x: usize, y: usize, status: BoardStatus) {
+    self.faulted[x][y] = status == BoardStatus::Faulted;
+  }
+
+  pub(crate) fn faulted(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.faulted.iter().enumerate().flat_map(|(x, row)| {
+      row
+        .iter()
+        .enumerate()
+        .filter(|(_, faulted)| **faulted)
+        .map(move |(y, _)| (x, y))
+    })
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.faulted = [[false; H]; W];
+  }
+
+  /// Whether the board at `(x, y)` is not to be accessed.
+  pub(crate) fn skip(&self, x: usize, y: usize) -> bool {
+    self.tolerant && self.faulted[x][y]
+  }
+
+  /// Records the outcome of an operation on the board at `(x, y)`. In fault
+  /// tolerant mode an error is swallowed and gives `Ok(None)`.
+  pub(crate) fn check<T, E>(
+    &mut self,
+    x: usize,
+    y: usize,
+    result: Result<T, Error<E>>,
+  ) -> Result<Option<T>, Error<E>> {
+    self.faulted[x][y] = result.is_err();
+    match result {
+      Ok(value) => Ok(Some(value)),
+      Err(_) if self.tolerant => Ok(None),
+      Err(e) => Err(e),
+    }
+  }
+}
@@ -0,0 +1,66 @@
+use crate::Color;
+
+/// In-memory copy of the pixels of a `W` x `H` grid of boards.
+///
+/// Pixels can be set freely without any bus traffic, the whole frame is then sent
+/// at once with `NeoTrellis::flush` or `MultiTrellis::flush`.
+#[derive(Clone)]
+pub struct Framebuffer<const W: usize = 1, const H: usize = 1> {
+  pixels: [[[Color; 16]; H]; W],
+}
+
+impl<const W: usize, const H: usize> Framebuffer<W, H> {
+  pub const fn new() -> Self {
+    Self {
+      pixels: [[[Color::BLACK; 16]; H]; W],
+    }
+  }
+
+  pub const fn width(&self) -> usize {
+    4 * W
+  }
+
+  pub const fn height(&self) -> usize {
+    4 * H
+  }
+
+  /// Sets the pixel at `(x, y)`, coordinates outside of the grid are ignored.
+  pub fn set(&mut self, x: u8, y: u8, color: Color) {
+    let (x, y) = (usize::from(x), usize::from(y));
+    if x < self.width() && y < self.height() {
+      self.pixels[x / 4][y / 4][x % 4 + (y % 4) * 4] = color;
+    }
+  }
+
+  pub fn get(&self, x: u8, y: u8) -> Option<Color> {
+    let (x, y) = (usize::from(x), usize::from(y));
+    if x < self.width() && y < self.height() {
+      Some(self.pixels[x / 4][y / 4][x % 4 + (y % 4) * 4])
+    } else {
+      None
+    }
+  }
+
+  pub fn fill(&mut self, color: Color) {
+    for row in self.pixels.iter_mut() {
+      for board in row.iter_mut() {
+        *board = [color; 16];
+      }
+    }
+  }
+
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Pixels of the board at grid position `(x, y)`, in LED index order.
+  pub(crate) fn board(&self, x: usize, y: usize) -> &[Color; 16] {
+    &self.pixels[x][y]
+  }
+}
+
+impl<const W: usize, const H: usize> Default for Framebuffer<W, H> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,185 @@
+//! `embedded-graphics` integration: the LEDs are the pixels of a draw target, one
+//! pixel per key. Drawing only updates the local pixel buffer, `show_led` or
+//! `MultiTrellis::show` sends it.
+
+use core::convert::{Infallible, TryFrom};
+
+use embedded_graphics_core::draw_target::DrawTarget;
+#[cfg(feature = "embedded-graphics-simulator")]
+use embedded_graphics_core::geometry::Point;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics_core::Pixel;
+#[cfg(feature = "embedded-graphics-simulator")]
+use embedded_graphics_simulator::SimulatorDisplay;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::{Color, Error, Framebuffer, HasNeopixel, NeoTrellis, TrellisDevice, TrellisGrid};
+
+impl From<Rgb888> for Color {
+  fn from(color: Rgb888) -> Self {
+    Color::rgb(color.r(), color.g(), color.b())
+  }
+}
+
+impl From<Color> for Rgb888 {
+  fn from(color: Color) -> Self {
+    Rgb888::new(color.r, color.g, color.b)
+  }
+}
+
+/// Drawing into a framebuffer needs no bus, so frames can be prepared ahead and
+/// sent with `flush`.
+impl<const W: usize, const H: usize> DrawTarget for Framebuffer<W, H> {
+  type Color = Rgb888;
+  type Error = Infallible;
+
+  fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+  where
+    P: IntoIterator<Item = Pixel<Rgb888>>,
+  {
+    for Pixel(point, color) in pixels {
+      if let (Ok(x), Ok(y)) = (u8::try_from(point.x), u8::try_from(point.y)) {
+        self.set(x, y, color.into());
+      }
+    }
+
+    Ok(())
+  }
+
+  fn clear(&mut self, color: Rgb888) -> Result<(), Self::Error> {
+    self.fill(color.into());
+
+    Ok(())
+  }
+}
+
+impl<const W: usize, const H: usize> OriginDimensions for Framebuffer<W, H> {
+  fn size(&self) -> Size {
+    Size::new(self.width() as u32, self.height() as u32)
+  }
+}
+
+/// Previews on an `embedded-graphics-simulator` display, one display pixel per key.
+/// The display can be exported as an image, or shown in a window with the
+/// simulator's `with-sdl` feature.
+///
+/// ```
+/// use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+/// use embedded_graphics_core::prelude::*;
+/// use embedded_graphics_simulator::OutputSettingsBuilder;
+/// use neotrellis::Framebuffer;
+///
+/// let mut framebuffer: Framebuffer<2, 1> = Framebuffer::new();
+/// framebuffer.draw_iter([Pixel(Point::new(5, 1), Rgb888::RED)]).unwrap();
+///
+/// let display = framebuffer.simulator_display();
+/// assert_eq!(display.get_pixel(Point::new(5, 1)), Rgb888::RED);
+///
+/// // 40 x 40 image pixels per key, e.g. for `save_png`
+/// let image = display.to_rgb_output_image(&OutputSettingsBuilder::new().scale(40).build());
+/// assert_eq!(image.as_image_buffer().width(), 8 * 40);
+/// ```
+#[cfg(feature = "embedded-graphics-simulator")]
+impl<const W: usize, const H: usize> Framebuffer<W, H> {
+  /// A simulator display as large as the framebuffer.
+  pub fn simulator_display(&self) -> SimulatorDisplay<Rgb888> {
+    let mut display = SimulatorDisplay::new(OriginDimensions::size(self));
+    self.mirror_to(&mut display);
+
+    display
+  }
+
+  /// Copies every pixel to `display`, the ones outside of it are dropped.
+  pub fn mirror_to(&self, display: &mut SimulatorDisplay<Rgb888>) {
+    let (width, height) = (self.width() as u8, self.height() as u8);
+    let pixels = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+    let result = display.draw_iter(pixels.filter_map(|(x, y)| {
+      let color = self.get(x, y)?;
+      Some(Pixel(Point::new(x.into(), y.into()), color.into()))
+    }));
+
+    // Drawing on a simulator display can't fail
+    result.unwrap_or_else(|error| match error {});
+  }
+}
+
+/// A 4x4 target, pixels outside of the board are dropped.
+impl<I2C, D, S> DrawTarget for NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasNeopixel,
+{
+  type Color = Rgb888;
+  type Error = Error<I2C::Error>;
+
+  fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+  where
+    P: IntoIterator<Item = Pixel<Rgb888>>,
+  {
+    for Pixel(point, color) in pixels {
+      if (0..4).contains(&point.x) && (0..4).contains(&point.y) {
+        self.set_led_color((point.x + 4 * point.y) as u8, color.into())?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn clear(&mut self, color: Rgb888) -> Result<(), Self::Error> {
+    self.fill(color.into());
+
+    Ok(())
+  }
+}
+
+impl<I2C, D, S> OriginDimensions for NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasNeopixel,
+{
+  fn size(&self) -> Size {
+    Size::new(4, 4)
+  }
+}
+
+/// Covers the bounding box of the layout, pixels that no board covers are dropped.
+impl<T, E, const W: usize, const H: usize> DrawTarget for TrellisGrid<T, W, H>
+where
+  T: TrellisDevice<Error = Error<E>>,
+{
+  type Color = Rgb888;
+  type Error = Error<E>;
+
+  fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+  where
+    P: IntoIterator<Item = Pixel<Rgb888>>,
+  {
+    for Pixel(point, color) in pixels {
+      if let (Ok(x), Ok(y)) = (u8::try_from(point.x), u8::try_from(point.y)) {
+        match self.set_led_color((x, y), color.into()) {
+          Err(Error::OutOfBounds) => {}
+          result => result?,
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  fn clear(&mut self, color: Rgb888) -> Result<(), Self::Error> {
+    self.fill(color.into());
+
+    Ok(())
+  }
+}
+
+impl<T, const W: usize, const H: usize> OriginDimensions for TrellisGrid<T, W, H> {
+  fn size(&self) -> Size {
+    let (width, height) = self.layout.size();
+    Size::new(width.into(), height.into())
+  }
+}
@@ -0,0 +1,164 @@
+use heapless::Vec;
+
+use super::ButtonEvent;
+
+/// Keys pressed together, in the order they were pressed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chord<const N: usize> {
+  keys: Vec<(u8, u8), N>,
+}
+
+impl<const N: usize> Chord<N> {
+  /// Positions of the keys.
+  pub fn keys(&self) -> &[(u8, u8)] {
+    &self.keys
+  }
+
+  pub fn len(&self) -> usize {
+    self.keys.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.keys.is_empty()
+  }
+
+  pub fn contains(&self, position: (u8, u8)) -> bool {
+    self.keys.contains(&position)
+  }
+
+  /// Whether the chord is made of exactly `keys`, in any order.
+  pub fn matches(&self, keys: &[(u8, u8)]) -> bool {
+    keys.len() == self.keys.len() && keys.iter().all(|key| self.keys.contains(key))
+  }
+}
+
+/// Groups the key presses happening within `window_ms` of each other into chords
+/// of up to `N` keys.
+///
+/// A chord ends when the window after its first press is over, when one of its
+/// keys is released or when a key is pressed after the window. A single press
+/// makes a chord of one key, presses beyond `N` keys are left out of the chord.
+///
+/// ```ignore
+/// let mut chords: ChordDetector = ChordDetector::new(50);
+/// trellis.drain_events(|event| {
+///   if let Some(chord) = chords.update(event, now_ms()) {
+///     handle(chord);
+///   }
+/// })?;
+/// if let Some(chord) = chords.poll(now_ms()) {
+///   handle(chord);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ChordDetector<const N: usize = 4> {
+  window_ms: u32,
+  started_at: u32,
+  pending: Vec<(u8, u8), N>,
+}
+
+impl<const N: usize> ChordDetector<N> {
+  pub const fn new(window_ms: u32) -> Self {
+    Self {
+      window_ms,
+      started_at: 0,
+      pending: Vec::new(),
+    }
+  }
+
+  pub fn window(&self) -> u32 {
+    self.window_ms
+  }
+
+  pub fn set_window(&mut self, window_ms: u32) {
+    self.window_ms = window_ms;
+  }
+
+  /// Feeds one event read at `now_ms`, returning the chord it completes.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) -> Option<Chord<N>> {
+    let position = event.position();
+    if event.is_press() {
+      let chord = self.poll(now_ms);
+      if self.pending.is_empty() {
+        self.started_at = now_ms;
+      }
+      if !self.pending.contains(&position) {
+        let _ = self.pending.push(position);
+      }
+      chord
+    } else if event.is_release() && self.pending.contains(&position) {
+      self.take()
+    } else {
+      None
+    }
+  }
+
+  /// Returns the pending chord once its window is over.
+  pub fn poll(&mut self, now_ms: u32) -> Option<Chord<N>> {
+    if now_ms.wrapping_sub(self.started_at) > self.window_ms {
+      self.take()
+    } else {
+      None
+    }
+  }
+
+  fn take(&mut self) -> Option<Chord<N>> {
+    if self.pending.is_empty() {
+      None
+    } else {
+      Some(Chord {
+        keys: core::mem::take(&mut self.pending),
+      })
+    }
+  }
+}
+
+impl<const N: usize> Default for ChordDetector<N> {
+  /// A 50 ms window.
+  fn default() -> Self {
+    Self::new(50)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ChordDetector;
+  use crate::input::{press, release};
+
+  #[test]
+  fn presses_within_the_window_make_one_chord() {
+    let mut chords: ChordDetector = ChordDetector::new(50);
+    assert!(chords.update(press((0, 0)), 100).is_none());
+    assert!(chords.update(press((1, 0)), 150).is_none());
+    assert!(chords.poll(150).is_none());
+    let chord = chords.poll(151).unwrap();
+    assert!(chord.matches(&[(1, 0), (0, 0)]));
+    assert!(chords.poll(200).is_none());
+  }
+
+  #[test]
+  fn press_after_the_window_starts_the_next_chord() {
+    let mut chords: ChordDetector = ChordDetector::new(50);
+    chords.update(press((0, 0)), 100);
+    let chord = chords.update(press((1, 0)), 151).unwrap();
+    assert_eq!(chord.keys(), &[(0, 0)]);
+    assert_eq!(chords.poll(202).unwrap().keys(), &[(1, 0)]);
+  }
+
+  #[test]
+  fn release_ends_the_chord() {
+    let mut chords: ChordDetector = ChordDetector::new(50);
+    chords.update(press((0, 0)), 100);
+    chords.update(press((0, 1)), 110);
+    assert_eq!(chords.update(release((0, 1)), 120).unwrap().len(), 2);
+  }
+
+  #[test]
+  fn presses_beyond_n_keys_are_left_out() {
+    let mut chords: ChordDetector<2> = ChordDetector::new(50);
+    chords.update(press((0, 0)), 100);
+    chords.update(press((1, 0)), 101);
+    chords.update(press((2, 0)), 102);
+    assert_eq!(chords.poll(200).unwrap().keys(), &[(0, 0), (1, 0)]);
+  }
+}
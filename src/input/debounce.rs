@@ -0,0 +1,168 @@
+use super::{ButtonEvent, KeyGrid};
+use crate::Event;
+
+#[derive(Clone, Copy)]
+struct KeyState {
+  /// State reported to the application.
+  pressed: bool,
+  /// Last state seen on the keypad.
+  raw: bool,
+  /// Time of the last reported edge.
+  changed_at: u32,
+}
+
+/// Filters the edge events of a `W` x `H` grid of boards, suppressing the
+/// press/release pairs caused by contact bounce or noise.
+///
+/// The first edge of a key is reported immediately, the edges following it within
+/// `window_ms` are swallowed. If the key ended up in a different state once the
+/// window is over, `poll` reports that change, so a key never stays stuck.
+/// Level events are passed through unchanged.
+///
+/// ```ignore
+/// let mut debouncer: Debouncer = Debouncer::new(20);
+/// trellis.drain_events(|event| {
+///   if let Some(event) = debouncer.update(event, now_ms()) {
+///     handle(event);
+///   }
+/// })?;
+/// while let Some(event) = debouncer.poll::<KeypadEvent>(now_ms()) {
+///   handle(event);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Debouncer<const W: usize = 1, const H: usize = 1> {
+  window_ms: u32,
+  keys: KeyGrid<Option<KeyState>, W, H>,
+}
+
+impl<const W: usize, const H: usize> Debouncer<W, H> {
+  pub const fn new(window_ms: u32) -> Self {
+    Self {
+      window_ms,
+      keys: KeyGrid::new(None),
+    }
+  }
+
+  pub fn window(&self) -> u32 {
+    self.window_ms
+  }
+
+  pub fn set_window(&mut self, window_ms: u32) {
+    self.window_ms = window_ms;
+  }
+
+  /// Debounced state of the key at `position`.
+  pub fn is_pressed(&self, position: (u8, u8)) -> bool {
+    matches!(self.keys.get(position), Some(Some(state)) if state.pressed)
+  }
+
+  /// Feeds one event read at `now_ms`, returning it if it is not a bounce.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) -> Option<E> {
+    let pressed = match event.edge() {
+      Event::Rising => true,
+      Event::Falling => false,
+      Event::High | Event::Low => return Some(event),
+    };
+    let window_ms = self.window_ms;
+    let key = self.keys.get_mut(event.position())?;
+
+    match key {
+      None => {
+        *key = Some(KeyState {
+          pressed,
+          raw: pressed,
+          changed_at: now_ms,
+        });
+        Some(event)
+      }
+      Some(state) => {
+        state.raw = pressed;
+        if now_ms.wrapping_sub(state.changed_at) < window_ms || state.pressed == pressed {
+          None
+        } else {
+          state.pressed = pressed;
+          state.changed_at = now_ms;
+          Some(event)
+        }
+      }
+    }
+  }
+
+  /// Returns the state changes that settled after their window, one per call.
+  pub fn poll<E: ButtonEvent>(&mut self, now_ms: u32) -> Option<E> {
+    let window_ms = self.window_ms;
+    self.keys.iter_mut().find_map(|(position, key)| match key {
+      Some(state)
+        if state.raw != state.pressed && now_ms.wrapping_sub(state.changed_at) >= window_ms =>
+      {
+        state.pressed = state.raw;
+        state.changed_at = now_ms;
+        let edge = if state.pressed {
+          Event::Rising
+        } else {
+          Event::Falling
+        };
+        Some(E::from_parts(position, edge))
+      }
+      _ => None,
+    })
+  }
+
+  /// Forgets the state of all keys.
+  pub fn reset(&mut self) {
+    self.keys.fill(None);
+  }
+}
+
+impl<const W: usize, const H: usize> Default for Debouncer<W, H> {
+  /// A 10 ms window.
+  fn default() -> Self {
+    Self::new(10)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Debouncer;
+  use crate::input::{parts, press, release, ButtonEvent};
+  use crate::{Event, MultiEvent};
+
+  #[test]
+  fn edges_within_the_window_are_bounces() {
+    let mut debouncer: Debouncer = Debouncer::new(20);
+    assert_eq!(
+      parts(debouncer.update(press((1, 2)), 100)),
+      Some(((1, 2), Event::Rising))
+    );
+    assert!(debouncer.update(release((1, 2)), 105).is_none());
+    assert!(debouncer.update(press((1, 2)), 119).is_none());
+    assert!(debouncer.is_pressed((1, 2)));
+    assert_eq!(
+      parts(debouncer.update(release((1, 2)), 120)),
+      Some(((1, 2), Event::Falling))
+    );
+  }
+
+  #[test]
+  fn state_settles_once_the_window_is_over() {
+    let mut debouncer: Debouncer = Debouncer::new(20);
+    debouncer.update(press((0, 0)), 100);
+    assert!(debouncer.update(release((0, 0)), 110).is_none());
+    assert!(debouncer.poll::<MultiEvent>(119).is_none());
+    assert_eq!(
+      parts(debouncer.poll::<MultiEvent>(120)),
+      Some(((0, 0), Event::Falling))
+    );
+    assert!(debouncer.poll::<MultiEvent>(200).is_none());
+    assert!(!debouncer.is_pressed((0, 0)));
+  }
+
+  #[test]
+  fn levels_pass_through() {
+    let mut debouncer: Debouncer = Debouncer::new(20);
+    let held = MultiEvent::from_parts((0, 0), Event::High);
+    debouncer.update(press((0, 0)), 100);
+    assert!(debouncer.update(held, 101).is_some());
+  }
+}
@@ -0,0 +1,193 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use heapless::Vec;
+
+use super::ButtonEvent;
+use crate::{Error, KeypadEvent, MultiEvent, NeoTrellis, TrellisDevice};
+
+/// A rectangle of keys, `width` x `height` keys starting at `(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+  pub x: u8,
+  pub y: u8,
+  pub width: u8,
+  pub height: u8,
+}
+
+impl Region {
+  pub const fn new(x: u8, y: u8, width: u8, height: u8) -> Self {
+    Self {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  /// The single key at `position`.
+  pub const fn key(position: (u8, u8)) -> Self {
+    Self::new(position.0, position.1, 1, 1)
+  }
+
+  pub fn contains(&self, (x, y): (u8, u8)) -> bool {
+    let (x, y) = (u16::from(x), u16::from(y));
+    let (rx, ry) = (u16::from(self.x), u16::from(self.y));
+    rx <= x && x < rx + u16::from(self.width) && ry <= y && y < ry + u16::from(self.height)
+  }
+}
+
+/// Routes key events to the handlers registered for the key or region they
+/// happened in, up to `N` handlers.
+///
+/// Handlers are tried in registration order and only the first one whose region
+/// contains the key is called, the fallback handler receives the events no region
+/// matched.
+///
+/// ```ignore
+/// let mut play = |event: KeypadEvent| start_playback(event);
+/// let mut pads = |event: KeypadEvent| trigger(event.key);
+/// let mut dispatcher: Dispatcher<KeypadEvent> = Dispatcher::new();
+/// let registered = dispatcher
+///   .on_key((0, 0), &mut play)
+///   .and_then(|dispatcher| dispatcher.on_region(Region::new(0, 1, 4, 3), &mut pads));
+/// assert!(registered.is_ok(), "too many handlers");
+/// loop {
+///   dispatcher.poll(&mut trellis)?;
+/// }
+/// ```
+pub struct Dispatcher<'a, E, const N: usize = 8> {
+  handlers: Vec<(Region, &'a mut dyn FnMut(E)), N>,
+  fallback: Option<&'a mut dyn FnMut(E)>,
+}
+
+impl<'a, E: ButtonEvent, const N: usize> Dispatcher<'a, E, N> {
+  pub fn new() -> Self {
+    Self {
+      handlers: Vec::new(),
+      fallback: None,
+    }
+  }
+
+  /// Registers `handler` for the events of the keys in `region`.
+  ///
+  /// If `N` handlers are registered already, `handler` is handed back as the error.
+  pub fn on_region(
+    &mut self,
+    region: Region,
+    handler: &'a mut dyn FnMut(E),
+  ) -> Result<&mut Self, &'a mut dyn FnMut(E)> {
+    match self.handlers.push((region, handler)) {
+      Ok(()) => Ok(self),
+      Err((_, handler)) => Err(handler),
+    }
+  }
+
+  /// Registers `handler` for the events of the key at `position`, see `on_region`.
+  pub fn on_key(
+    &mut self,
+    position: (u8, u8),
+    handler: &'a mut dyn FnMut(E),
+  ) -> Result<&mut Self, &'a mut dyn FnMut(E)> {
+    self.on_region(Region::key(position), handler)
+  }
+
+  /// Registers `handler` for the events no other handler takes.
+  pub fn on_unhandled(&mut self, handler: &'a mut dyn FnMut(E)) -> &mut Self {
+    self.fallback = Some(handler);
+    self
+  }
+
+  /// Calls the handler for `event`, returning whether there was one.
+  pub fn dispatch(&mut self, event: E) -> bool {
+    let position = event.position();
+    let handler = self
+      .handlers
+      .iter_mut()
+      .find(|(region, _)| region.contains(position))
+      .map(|(_, handler)| handler)
+      .or(self.fallback.as_mut());
+
+    match handler {
+      Some(handler) => {
+        handler(event);
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+impl<'a, const N: usize> Dispatcher<'a, KeypadEvent, N> {
+  /// Drains the keypad FIFO of `trellis`, dispatching every event.
+  pub fn poll<I2C, D>(
+    &mut self,
+    trellis: &mut NeoTrellis<I2C, D>,
+  ) -> Result<usize, Error<I2C::Error>>
+  where
+    I2C: I2c,
+    D: DelayNs,
+  {
+    trellis.drain_events(|event| {
+      self.dispatch(event);
+    })
+  }
+}
+
+impl<'a, const N: usize> Dispatcher<'a, MultiEvent, N> {
+  /// Drains the key events of `trellis`, e.g. all boards of a `MultiTrellis`,
+  /// dispatching every event.
+  pub fn poll<T: TrellisDevice>(&mut self, trellis: &mut T) -> Result<usize, T::Error> {
+    trellis.drain_events(|event| {
+      self.dispatch(event);
+    })
+  }
+}
+
+impl<'a, E: ButtonEvent, const N: usize> Default for Dispatcher<'a, E, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Dispatcher, Region};
+  use crate::input::press;
+  use crate::MultiEvent;
+
+  #[test]
+  fn first_matching_handler_takes_the_event() {
+    let (mut key, mut region, mut unhandled) = (0, 0, 0);
+    let mut on_key = |_: MultiEvent| key += 1;
+    let mut on_region = |_: MultiEvent| region += 1;
+    let mut on_unhandled = |_: MultiEvent| unhandled += 1;
+    let mut dispatcher: Dispatcher<MultiEvent> = Dispatcher::new();
+    assert!(dispatcher.on_key((0, 1), &mut on_key).is_ok());
+    assert!(dispatcher
+      .on_region(Region::new(0, 1, 4, 3), &mut on_region)
+      .is_ok());
+    dispatcher.on_unhandled(&mut on_unhandled);
+    assert!(dispatcher.dispatch(press((0, 1))));
+    assert!(dispatcher.dispatch(press((3, 3))));
+    assert!(dispatcher.dispatch(press((0, 0))));
+    drop(dispatcher);
+    assert_eq!((key, region, unhandled), (1, 1, 1));
+  }
+
+  #[test]
+  fn full_dispatcher_hands_the_handler_back() {
+    let mut first = |_: MultiEvent| {};
+    let mut second = |_: MultiEvent| {};
+    let mut dispatcher: Dispatcher<MultiEvent, 1> = Dispatcher::new();
+    assert!(dispatcher.on_key((0, 0), &mut first).is_ok());
+    assert!(dispatcher.on_key((1, 0), &mut second).is_err());
+  }
+
+  #[test]
+  fn region_bounds() {
+    let region = Region::new(254, 1, 2, 1);
+    assert!(region.contains((255, 1)));
+    assert!(!region.contains((253, 1)));
+    assert!(!region.contains((254, 2)));
+  }
+}
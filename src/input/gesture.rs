@@ -0,0 +1,199 @@
+use super::ButtonEvent;
+
+/// Direction of a swipe, `Up` goes towards row 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+  Up,
+  Down,
+  Left,
+  Right,
+}
+
+impl Direction {
+  /// Direction of the step from `from` to the adjacent key `to`.
+  fn between(from: (u8, u8), to: (u8, u8)) -> Option<Self> {
+    let (fx, fy) = (i16::from(from.0), i16::from(from.1));
+    let (tx, ty) = (i16::from(to.0), i16::from(to.1));
+    match (tx - fx, ty - fy) {
+      (0, -1) => Some(Direction::Up),
+      (0, 1) => Some(Direction::Down),
+      (-1, 0) => Some(Direction::Left),
+      (1, 0) => Some(Direction::Right),
+      _ => None,
+    }
+  }
+}
+
+/// A straight run of adjacent presses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Swipe {
+  pub direction: Direction,
+  /// Number of keys covered, including the first.
+  pub length: u8,
+  pub start: (u8, u8),
+  pub end: (u8, u8),
+}
+
+#[derive(Clone, Copy)]
+struct Track {
+  start: (u8, u8),
+  end: (u8, u8),
+  direction: Option<Direction>,
+  length: u8,
+  last_press: u32,
+}
+
+/// Recognizes swipes: presses of adjacent keys along a row or a column, each
+/// following the previous one within `max_interval_ms`.
+///
+/// Works with the events of a single board as well as with the global coordinates
+/// of a `MultiTrellis`, so swipes can cross from one board to the next. Only swipes
+/// covering at least `min_length` keys are reported.
+///
+/// ```ignore
+/// let mut swipes = SwipeDetector::new(150, 3);
+/// trellis.drain_events(|event| {
+///   if let Some(swipe) = swipes.update(event, now_ms()) {
+///     handle(swipe);
+///   }
+/// })?;
+/// if let Some(swipe) = swipes.poll(now_ms()) {
+///   handle(swipe);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SwipeDetector {
+  max_interval_ms: u32,
+  min_length: u8,
+  track: Option<Track>,
+}
+
+impl SwipeDetector {
+  pub const fn new(max_interval_ms: u32, min_length: u8) -> Self {
+    Self {
+      max_interval_ms,
+      min_length,
+      track: None,
+    }
+  }
+
+  pub fn max_interval(&self) -> u32 {
+    self.max_interval_ms
+  }
+
+  pub fn set_max_interval(&mut self, max_interval_ms: u32) {
+    self.max_interval_ms = max_interval_ms;
+  }
+
+  pub fn min_length(&self) -> u8 {
+    self.min_length
+  }
+
+  pub fn set_min_length(&mut self, min_length: u8) {
+    self.min_length = min_length;
+  }
+
+  /// Feeds one event read at `now_ms`, returning the swipe it ends, if any.
+  ///
+  /// A press that does not continue the current swipe ends it and may start the
+  /// next one.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) -> Option<Swipe> {
+    if !event.is_press() {
+      return None;
+    }
+    let position = event.position();
+    let swipe = self.poll(now_ms);
+
+    if let Some(track) = &mut self.track {
+      let direction = Direction::between(track.end, position);
+      if direction.is_some() && (track.direction.is_none() || track.direction == direction) {
+        track.direction = direction;
+        track.end = position;
+        track.length = track.length.saturating_add(1);
+        track.last_press = now_ms;
+        return None;
+      }
+    }
+
+    let swipe = swipe.or_else(|| self.finish());
+    self.track = Some(Track {
+      start: position,
+      end: position,
+      direction: None,
+      length: 1,
+      last_press: now_ms,
+    });
+    swipe
+  }
+
+  /// Returns the current swipe once no key continued it for `max_interval_ms`.
+  pub fn poll(&mut self, now_ms: u32) -> Option<Swipe> {
+    match self.track {
+      Some(track) if now_ms.wrapping_sub(track.last_press) > self.max_interval_ms => self.finish(),
+      _ => None,
+    }
+  }
+
+  fn finish(&mut self) -> Option<Swipe> {
+    let track = self.track.take()?;
+    match track.direction {
+      Some(direction) if track.length >= self.min_length => Some(Swipe {
+        direction,
+        length: track.length,
+        start: track.start,
+        end: track.end,
+      }),
+      _ => None,
+    }
+  }
+}
+
+impl Default for SwipeDetector {
+  /// At most 150 ms between presses, at least 3 keys.
+  fn default() -> Self {
+    Self::new(150, 3)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Direction, Swipe, SwipeDetector};
+  use crate::input::press;
+
+  #[test]
+  fn swipe_ends_after_the_interval() {
+    let mut swipes = SwipeDetector::new(150, 3);
+    assert!(swipes.update(press((0, 1)), 0).is_none());
+    assert!(swipes.update(press((1, 1)), 150).is_none());
+    assert!(swipes.update(press((2, 1)), 300).is_none());
+    assert!(swipes.poll(450).is_none());
+    assert_eq!(
+      swipes.poll(451),
+      Some(Swipe {
+        direction: Direction::Right,
+        length: 3,
+        start: (0, 1),
+        end: (2, 1),
+      })
+    );
+  }
+
+  #[test]
+  fn late_press_breaks_the_swipe() {
+    let mut swipes = SwipeDetector::new(150, 3);
+    swipes.update(press((0, 3)), 0);
+    swipes.update(press((0, 2)), 100);
+    assert!(swipes.update(press((0, 1)), 251).is_none());
+    assert!(swipes.poll(1000).is_none());
+  }
+
+  #[test]
+  fn turn_ends_the_swipe() {
+    let mut swipes = SwipeDetector::new(150, 2);
+    swipes.update(press((0, 3)), 0);
+    swipes.update(press((0, 2)), 10);
+    let swipe = swipes.update(press((1, 2)), 20).unwrap();
+    assert_eq!(swipe.direction, Direction::Up);
+    assert_eq!(swipe.length, 2);
+  }
+}
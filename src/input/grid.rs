@@ -0,0 +1,81 @@
+/// One `T` per key of a `W` x `H` grid of boards, laid out like a `Framebuffer`.
+#[derive(Clone)]
+pub(crate) struct KeyGrid<T, const W: usize, const H: usize> {
+  keys: [[[T; 16]; H]; W],
+}
+
+impl<T: Copy, const W: usize, const H: usize> KeyGrid<T, W, H> {
+  pub(crate) const fn new(value: T) -> Self {
+    Self {
+      keys: [[[value; 16]; H]; W],
+    }
+  }
+
+  pub(crate) fn get(&self, (x, y): (u8, u8)) -> Option<&T> {
+    let (x, y) = (usize::from(x), usize::from(y));
+    self
+      .keys
+      .get(x / 4)
+      .and_then(|column| column.get(y / 4))
+      .map(|board| &board[x % 4 + (y % 4) * 4])
+  }
+
+  pub(crate) fn get_mut(&mut self, (x, y): (u8, u8)) -> Option<&mut T> {
+    let (x, y) = (usize::from(x), usize::from(y));
+    self
+      .keys
+      .get_mut(x / 4)
+      .and_then(|column| column.get_mut(y / 4))
+      .map(|board| &mut board[x % 4 + (y % 4) * 4])
+  }
+
+  /// Iterates over all keys together with their position.
+  pub(crate) fn iter(&self) -> impl Iterator<Item = ((u8, u8), &T)> {
+    self.keys.iter().enumerate().flat_map(|(bx, column)| {
+      column.iter().enumerate().flat_map(move |(by, board)| {
+        board.iter().enumerate().map(move |(i, key)| {
+          let position = (4 * bx + i % 4, 4 * by + i / 4);
+          ((position.0 as u8, position.1 as u8), key)
+        })
+      })
+    })
+  }
+
+  /// Mutable version of `iter`.
+  pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = ((u8, u8), &mut T)> {
+    self.keys.iter_mut().enumerate().flat_map(|(bx, column)| {
+      column.iter_mut().enumerate().flat_map(move |(by, board)| {
+        board.iter_mut().enumerate().map(move |(i, key)| {
+          let position = (4 * bx + i % 4, 4 * by + i / 4);
+          ((position.0 as u8, position.1 as u8), key)
+        })
+      })
+    })
+  }
+
+  pub(crate) fn fill(&mut self, value: T) {
+    self.keys = [[[value; 16]; H]; W];
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::KeyGrid;
+
+  #[test]
+  fn positions_span_the_boards() {
+    let mut grid: KeyGrid<u8, 2, 1> = KeyGrid::new(0);
+    *grid.get_mut((5, 2)).unwrap() = 1;
+    assert_eq!(grid.get((5, 2)), Some(&1));
+    assert_eq!(grid.get((8, 0)), None);
+    assert_eq!(grid.get((0, 4)), None);
+    assert_eq!(grid.iter().count(), 32);
+    assert_eq!(
+      grid
+        .iter()
+        .find(|(_, key)| **key == 1)
+        .map(|(position, _)| position),
+      Some((5, 2))
+    );
+  }
+}
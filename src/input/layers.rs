@@ -0,0 +1,242 @@
+use super::{ButtonEvent, KeyGrid};
+use crate::{Color, Event, Framebuffer};
+
+/// Actions and optional LED colors of the keys of a `W` x `H` grid of boards for
+/// one layer.
+///
+/// Keys without an action are transparent and take the action of the base layer.
+#[derive(Clone)]
+pub struct Layer<A, const W: usize = 1, const H: usize = 1> {
+  actions: KeyGrid<Option<A>, W, H>,
+  colors: KeyGrid<Option<Color>, W, H>,
+}
+
+impl<A: Copy, const W: usize, const H: usize> Layer<A, W, H> {
+  /// A layer where all keys are transparent.
+  pub const fn new() -> Self {
+    Self {
+      actions: KeyGrid::new(None),
+      colors: KeyGrid::new(None),
+    }
+  }
+
+  /// Sets the action of the key at `position`.
+  pub fn with_action(mut self, position: (u8, u8), action: A) -> Self {
+    if let Some(slot) = self.actions.get_mut(position) {
+      *slot = Some(action);
+    }
+    self
+  }
+
+  /// Sets the color shown at `position` while the layer is active.
+  pub fn with_color(mut self, position: (u8, u8), color: Color) -> Self {
+    if let Some(slot) = self.colors.get_mut(position) {
+      *slot = Some(color);
+    }
+    self
+  }
+
+  pub fn action(&self, position: (u8, u8)) -> Option<A> {
+    self.actions.get(position).copied().flatten()
+  }
+
+  pub fn color(&self, position: (u8, u8)) -> Option<Color> {
+    self.colors.get(position).copied().flatten()
+  }
+}
+
+impl<A: Copy, const W: usize, const H: usize> Default for Layer<A, W, H> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// An event translated to the action of its key on the active layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerEvent<A> {
+  pub action: A,
+  pub event: Event,
+  /// Layer the action was taken from.
+  pub layer: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Modifier {
+  layer: usize,
+  held: bool,
+}
+
+/// `L` layers of actions switched by modifier keys, as in keyboard firmware.
+///
+/// Layer 0 is the base layer. While a modifier key is held its layer is active, the
+/// most recently pressed modifier wins. A release always resolves to the same
+/// action and layer as the press of the key, even if the layer changed meanwhile.
+///
+/// ```ignore
+/// let base = Layer::new().with_action((0, 0), Action::Play);
+/// let shift = Layer::new().with_action((0, 0), Action::Stop).with_color((0, 0), Color::RED);
+/// let mut layers: Layers<Action, 2> = Layers::new([base, shift]);
+/// layers.set_modifier((3, 3), 1);
+/// trellis.drain_events(|event| {
+///   if let Some(LayerEvent { action, event, .. }) = layers.update(event) {
+///     handle(action, event);
+///   }
+/// })?;
+/// ```
+#[derive(Clone)]
+pub struct Layers<A, const L: usize, const W: usize = 1, const H: usize = 1> {
+  layers: [Layer<A, W, H>; L],
+  modifiers: KeyGrid<Option<Modifier>, W, H>,
+  /// Layer each held key was pressed on.
+  pressed_on: KeyGrid<Option<usize>, W, H>,
+  active: usize,
+}
+
+impl<A: Copy, const L: usize, const W: usize, const H: usize> Layers<A, L, W, H> {
+  pub fn new(layers: [Layer<A, W, H>; L]) -> Self {
+    Self {
+      layers,
+      modifiers: KeyGrid::new(None),
+      pressed_on: KeyGrid::new(None),
+      active: 0,
+    }
+  }
+
+  /// Makes the key at `position` activate `layer` while it is held, instead of
+  /// producing actions.
+  ///
+  /// Panics if `layer` is not one of the `L` layers.
+  pub fn set_modifier(&mut self, position: (u8, u8), layer: usize) {
+    assert!(layer < L, "no such layer");
+    if let Some(modifier) = self.modifiers.get_mut(position) {
+      *modifier = Some(Modifier { layer, held: false });
+    }
+  }
+
+  /// Turns the key at `position` back into a regular key.
+  pub fn clear_modifier(&mut self, position: (u8, u8)) {
+    if let Some(modifier) = self.modifiers.get_mut(position) {
+      *modifier = None;
+    }
+    self.active = self.held_layer();
+  }
+
+  pub fn active_layer(&self) -> usize {
+    self.active
+  }
+
+  pub fn layer(&self, layer: usize) -> Option<&Layer<A, W, H>> {
+    self.layers.get(layer)
+  }
+
+  pub fn layer_mut(&mut self, layer: usize) -> Option<&mut Layer<A, W, H>> {
+    self.layers.get_mut(layer)
+  }
+
+  /// Action of the key at `position` on `layer`, falling back to the base layer.
+  pub fn resolve(&self, layer: usize, position: (u8, u8)) -> Option<A> {
+    self
+      .layers
+      .get(layer)
+      .and_then(|layer| layer.action(position))
+      .or_else(|| self.layers.first().and_then(|base| base.action(position)))
+  }
+
+  /// Translates one event, modifier keys and keys without an action give `None`.
+  pub fn update<E: ButtonEvent>(&mut self, event: E) -> Option<LayerEvent<A>> {
+    let position = event.position();
+
+    if let Some(Some(modifier)) = self.modifiers.get_mut(position) {
+      if event.is_press() {
+        modifier.held = true;
+        self.active = modifier.layer;
+      } else if event.is_release() {
+        modifier.held = false;
+        if self.active == modifier.layer {
+          self.active = self.held_layer();
+        }
+      }
+      return None;
+    }
+
+    let layer = match (event.edge(), self.pressed_on.get_mut(position)) {
+      (Event::Rising, Some(pressed_on)) => {
+        *pressed_on = Some(self.active);
+        self.active
+      }
+      (Event::Falling, Some(pressed_on)) => pressed_on.take().unwrap_or(self.active),
+      _ => self.active,
+    };
+
+    self.resolve(layer, position).map(|action| LayerEvent {
+      action,
+      event: event.edge(),
+      layer,
+    })
+  }
+
+  /// Draws the colors of the active layer, falling back to the colors of the base
+  /// layer. Pixels without a color on either layer are left alone.
+  pub fn draw(&self, framebuffer: &mut Framebuffer<W, H>) {
+    let (active, base) = (&self.layers[self.active], &self.layers[0]);
+    for ((x, y), _) in self.modifiers.iter() {
+      if let Some(color) = active.color((x, y)).or_else(|| base.color((x, y))) {
+        framebuffer.set(x, y, color);
+      }
+    }
+  }
+
+  /// Layer of a modifier that is still held, or the base layer.
+  fn held_layer(&self) -> usize {
+    self
+      .modifiers
+      .iter()
+      .find_map(|(_, modifier)| modifier.filter(|modifier| modifier.held))
+      .map_or(0, |modifier| modifier.layer)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Layer, LayerEvent, Layers};
+  use crate::input::{press, release};
+  use crate::Event;
+
+  fn layers() -> Layers<char, 2> {
+    let base = Layer::new()
+      .with_action((0, 0), 'a')
+      .with_action((1, 0), 'b');
+    let shift = Layer::new().with_action((0, 0), 'A');
+    let mut layers = Layers::new([base, shift]);
+    layers.set_modifier((3, 3), 1);
+    layers
+  }
+
+  #[test]
+  fn modifier_switches_the_layer_while_held() {
+    let mut layers = layers();
+    assert_eq!(layers.update(press((3, 3))), None);
+    assert_eq!(layers.active_layer(), 1);
+    assert_eq!(
+      layers.update(press((0, 0))),
+      Some(LayerEvent {
+        action: 'A',
+        event: Event::Rising,
+        layer: 1,
+      })
+    );
+    assert_eq!(layers.update(press((1, 0))).map(|e| e.action), Some('b'));
+    layers.update(release((3, 3)));
+    assert_eq!(layers.active_layer(), 0);
+  }
+
+  #[test]
+  fn release_resolves_to_the_layer_of_the_press() {
+    let mut layers = layers();
+    layers.update(press((3, 3)));
+    layers.update(press((0, 0)));
+    layers.update(release((3, 3)));
+    let event = layers.update(release((0, 0))).unwrap();
+    assert_eq!((event.action, event.layer), ('A', 1));
+  }
+}
@@ -0,0 +1,132 @@
+use super::{ButtonEvent, KeyGrid};
+use crate::Event;
+
+#[derive(Clone, Copy)]
+struct KeyState {
+  held: bool,
+  /// Time of the last level event passed on, if any.
+  reported_at: Option<u32>,
+}
+
+/// Rate limits the level events of a `W` x `H` grid of boards, for "key currently
+/// held" sampling, see `EventSet::LEVELS`.
+///
+/// With level events enabled the seesaw queues an event for every key at every
+/// keypad scan, so the FIFO has to be read often and most events are repeats. At
+/// most one level event per key and `interval_ms` is passed on, edges are always
+/// passed on.
+///
+/// ```ignore
+/// trellis.set_key_events(EventSet::LEVELS)?;
+/// let mut sampler: LevelSampler = LevelSampler::new(50);
+/// trellis.drain_events(|event| {
+///   if let Some(event) = sampler.update(event, now_ms()) {
+///     handle(event);
+///   }
+/// })?;
+/// ```
+#[derive(Clone)]
+pub struct LevelSampler<const W: usize = 1, const H: usize = 1> {
+  interval_ms: u32,
+  keys: KeyGrid<KeyState, W, H>,
+}
+
+impl<const W: usize, const H: usize> LevelSampler<W, H> {
+  pub const fn new(interval_ms: u32) -> Self {
+    Self {
+      interval_ms,
+      keys: KeyGrid::new(KeyState {
+        held: false,
+        reported_at: None,
+      }),
+    }
+  }
+
+  pub fn interval(&self) -> u32 {
+    self.interval_ms
+  }
+
+  pub fn set_interval(&mut self, interval_ms: u32) {
+    self.interval_ms = interval_ms;
+  }
+
+  /// Whether the last event of the key at `position` said it is held.
+  pub fn is_held(&self, position: (u8, u8)) -> bool {
+    matches!(self.keys.get(position), Some(state) if state.held)
+  }
+
+  /// Feeds one event read at `now_ms`, returning it unless it repeats a level event
+  /// of the same key within `interval_ms`.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) -> Option<E> {
+    let interval_ms = self.interval_ms;
+    let state = self.keys.get_mut(event.position())?;
+    match event.edge() {
+      Event::Rising | Event::Falling => {
+        state.held = event.is_press();
+        Some(event)
+      }
+      Event::High | Event::Low => {
+        let held = event.is_held();
+        let due = match state.reported_at {
+          Some(reported_at) => now_ms.wrapping_sub(reported_at) >= interval_ms,
+          None => true,
+        };
+        if due || held != state.held {
+          state.held = held;
+          state.reported_at = Some(now_ms);
+          Some(event)
+        } else {
+          None
+        }
+      }
+    }
+  }
+
+  /// Forgets the state of all keys.
+  pub fn reset(&mut self) {
+    self.keys.fill(KeyState {
+      held: false,
+      reported_at: None,
+    });
+  }
+}
+
+impl<const W: usize, const H: usize> Default for LevelSampler<W, H> {
+  /// At most 20 level events a second per key.
+  fn default() -> Self {
+    Self::new(50)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::LevelSampler;
+  use crate::input::{press, release};
+  use crate::{Event, MultiEvent};
+
+  fn level(position: (u8, u8), held: bool) -> MultiEvent {
+    MultiEvent {
+      coordinate: position,
+      event: if held { Event::High } else { Event::Low },
+    }
+  }
+
+  #[test]
+  fn repeated_levels_are_passed_on_once_per_interval() {
+    let mut sampler: LevelSampler = LevelSampler::new(50);
+    assert!(sampler.update(level((0, 0), true), 100).is_some());
+    assert!(sampler.update(level((0, 0), true), 149).is_none());
+    assert!(sampler.update(level((0, 0), true), 150).is_some());
+    assert!(sampler.is_held((0, 0)));
+  }
+
+  #[test]
+  fn changed_levels_and_edges_are_passed_on_immediately() {
+    let mut sampler: LevelSampler = LevelSampler::new(50);
+    sampler.update(level((1, 1), true), 100);
+    assert!(sampler.update(level((1, 1), false), 101).is_some());
+    assert!(!sampler.is_held((1, 1)));
+    assert!(sampler.update(press((1, 1)), 102).is_some());
+    assert!(sampler.update(release((1, 1)), 103).is_some());
+  }
+}
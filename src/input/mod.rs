@@ -0,0 +1,106 @@
+//! Hardware independent helpers built on top of the keypad events.
+//!
+//! Apart from `Dispatcher::poll` these types don't talk to the bus, they take the
+//! events read from a `NeoTrellis` or a `MultiTrellis` and, where timing matters, a
+//! timestamp in milliseconds from a clock supplied by the application.
+
+mod chord;
+mod debounce;
+mod dispatch;
+mod gesture;
+mod grid;
+mod layers;
+mod level;
+mod radio;
+mod repeat;
+mod toggle;
+mod velocity;
+
+pub use chord::{Chord, ChordDetector};
+pub use debounce::Debouncer;
+pub use dispatch::{Dispatcher, Region};
+pub use gesture::{Direction, Swipe, SwipeDetector};
+pub(crate) use grid::KeyGrid;
+pub use layers::{Layer, LayerEvent, Layers};
+pub use level::LevelSampler;
+pub use radio::RadioGroup;
+pub use repeat::AutoRepeat;
+pub use toggle::ToggleGrid;
+pub use velocity::{VelocityEvent, VelocityTracker};
+
+use crate::{Event, Key, KeypadEvent, MultiEvent};
+
+/// A key event at a position of a grid of keys, implemented by `KeypadEvent`
+/// (a single board) and `MultiEvent` (a `MultiTrellis`).
+pub trait ButtonEvent: Copy {
+  /// Key position as `(x, y)`.
+  fn position(&self) -> (u8, u8);
+
+  fn edge(&self) -> Event;
+
+  /// Builds the event `edge` for the key at `position`.
+  fn from_parts(position: (u8, u8), edge: Event) -> Self;
+
+  fn is_press(&self) -> bool {
+    self.edge() == Event::Rising
+  }
+
+  fn is_release(&self) -> bool {
+    self.edge() == Event::Falling
+  }
+
+  /// Whether this is a level event reporting the key as held, see `LevelSampler`.
+  fn is_held(&self) -> bool {
+    self.edge() == Event::High
+  }
+}
+
+impl ButtonEvent for KeypadEvent {
+  fn position(&self) -> (u8, u8) {
+    (self.key.index() % 4, self.key.index() / 4)
+  }
+
+  fn edge(&self) -> Event {
+    self.event
+  }
+
+  fn from_parts((x, y): (u8, u8), edge: Event) -> Self {
+    KeypadEvent {
+      key: Key::from_index(x + 4 * y),
+      event: edge,
+    }
+  }
+}
+
+impl ButtonEvent for MultiEvent {
+  fn position(&self) -> (u8, u8) {
+    self.coordinate
+  }
+
+  fn edge(&self) -> Event {
+    self.event
+  }
+
+  fn from_parts(position: (u8, u8), edge: Event) -> Self {
+    MultiEvent {
+      coordinate: position,
+      event: edge,
+    }
+  }
+}
+
+#[cfg(test)]
+fn press(position: (u8, u8)) -> MultiEvent {
+  MultiEvent::from_parts(position, Event::Rising)
+}
+
+#[cfg(test)]
+fn release(position: (u8, u8)) -> MultiEvent {
+  MultiEvent::from_parts(position, Event::Falling)
+}
+
+/// Position and edge of an event, for comparing events in tests.
+#[cfg(test)]
+fn parts<E: ButtonEvent>(event: Option<E>) -> Option<((u8, u8), Event)> {
+  event.map(|event| (event.position(), event.edge()))
+}
@@ -0,0 +1,120 @@
+use super::ButtonEvent;
+use crate::{Color, Framebuffer};
+
+/// `N` keys of which at most one is selected: pressing a key of the group
+/// selects it and deselects the others, as in mode selector rows.
+///
+/// ```ignore
+/// let mut modes = RadioGroup::new([(0, 3), (1, 3), (2, 3), (3, 3)], Color::CYAN, Color::BLACK);
+/// trellis.drain_events(|event| {
+///   if let Some(mode) = modes.update(event) {
+///     set_mode(mode);
+///   }
+/// })?;
+/// modes.draw(&mut frame);
+/// ```
+#[derive(Clone)]
+pub struct RadioGroup<const N: usize = 4> {
+  keys: [(u8, u8); N],
+  selected: Option<usize>,
+  active_color: Color,
+  inactive_color: Color,
+}
+
+impl<const N: usize> RadioGroup<N> {
+  /// A group of the keys at `keys`, none of them selected.
+  pub const fn new(keys: [(u8, u8); N], active_color: Color, inactive_color: Color) -> Self {
+    Self {
+      keys,
+      selected: None,
+      active_color,
+      inactive_color,
+    }
+  }
+
+  pub fn keys(&self) -> &[(u8, u8)] {
+    &self.keys
+  }
+
+  /// Selects the key of a press event if it belongs to the group, returning its
+  /// index in the group.
+  pub fn update<E: ButtonEvent>(&mut self, event: E) -> Option<usize> {
+    if !event.is_press() {
+      return None;
+    }
+    let index = self.keys.iter().position(|key| *key == event.position())?;
+    self.selected = Some(index);
+    Some(index)
+  }
+
+  /// Index of the selected key.
+  pub fn selected(&self) -> Option<usize> {
+    self.selected
+  }
+
+  /// Position of the selected key.
+  pub fn selected_key(&self) -> Option<(u8, u8)> {
+    self.selected.map(|index| self.keys[index])
+  }
+
+  /// Selects the key at `index` in the group, `None` or an index out of range
+  /// deselect all keys.
+  pub fn select(&mut self, index: Option<usize>) {
+    self.selected = index.filter(|index| *index < self.keys.len());
+  }
+
+  pub fn set_colors(&mut self, active_color: Color, inactive_color: Color) {
+    self.active_color = active_color;
+    self.inactive_color = inactive_color;
+  }
+
+  pub fn colors(&self) -> (Color, Color) {
+    (self.active_color, self.inactive_color)
+  }
+
+  /// Highlights the selected key, the other keys of the group get the inactive
+  /// color and pixels outside of the group are left alone.
+  pub fn draw<const W: usize, const H: usize>(&self, framebuffer: &mut Framebuffer<W, H>) {
+    for (index, (x, y)) in self.keys.iter().enumerate() {
+      let color = if self.selected == Some(index) {
+        self.active_color
+      } else {
+        self.inactive_color
+      };
+      framebuffer.set(*x, *y, color);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::RadioGroup;
+  use crate::input::{press, release};
+  use crate::{Color, Framebuffer};
+
+  const ROW: [(u8, u8); 4] = [(0, 3), (1, 3), (2, 3), (3, 3)];
+
+  #[test]
+  fn press_selects_one_key_of_the_group() {
+    let mut modes = RadioGroup::new(ROW, Color::CYAN, Color::BLACK);
+    assert_eq!(modes.selected(), None);
+    assert_eq!(modes.update(press((2, 3))), Some(2));
+    assert_eq!(modes.update(release((2, 3))), None);
+    assert_eq!(modes.update(press((0, 0))), None);
+    assert_eq!(modes.selected_key(), Some((2, 3)));
+    modes.select(Some(4));
+    assert_eq!(modes.selected(), None);
+  }
+
+  #[test]
+  fn draw_leaves_other_pixels_alone() {
+    let mut modes = RadioGroup::new(ROW, Color::CYAN, Color::BLACK);
+    let mut frame: Framebuffer = Framebuffer::new();
+    frame.set(0, 0, Color::RED);
+    modes.select(Some(1));
+    modes.draw(&mut frame);
+    assert!(frame.get(1, 3) == Some(Color::CYAN));
+    assert!(frame.get(0, 3) == Some(Color::BLACK));
+    assert!(frame.get(0, 0) == Some(Color::RED));
+  }
+}
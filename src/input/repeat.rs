@@ -0,0 +1,125 @@
+use super::{ButtonEvent, KeyGrid};
+use crate::Event;
+
+/// Typematic auto-repeat for a `W` x `H` grid of boards: while a key is held,
+/// `poll` synthesizes a new press after `delay_ms` and then every `interval_ms`.
+///
+/// ```ignore
+/// let mut repeat: AutoRepeat = AutoRepeat::new(500, 100);
+/// trellis.drain_events(|event| {
+///   repeat.update(event, now_ms());
+///   handle(event);
+/// })?;
+/// while let Some(event) = repeat.poll::<KeypadEvent>(now_ms()) {
+///   handle(event);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AutoRepeat<const W: usize = 1, const H: usize = 1> {
+  delay_ms: u32,
+  interval_ms: u32,
+  /// Time of the next repeat of every held key.
+  keys: KeyGrid<Option<u32>, W, H>,
+}
+
+impl<const W: usize, const H: usize> AutoRepeat<W, H> {
+  pub const fn new(delay_ms: u32, interval_ms: u32) -> Self {
+    Self {
+      delay_ms,
+      interval_ms,
+      keys: KeyGrid::new(None),
+    }
+  }
+
+  pub fn delay(&self) -> u32 {
+    self.delay_ms
+  }
+
+  pub fn set_delay(&mut self, delay_ms: u32) {
+    self.delay_ms = delay_ms;
+  }
+
+  pub fn interval(&self) -> u32 {
+    self.interval_ms
+  }
+
+  pub fn set_interval(&mut self, interval_ms: u32) {
+    self.interval_ms = interval_ms;
+  }
+
+  /// Tracks the presses and releases, `event` itself is not repeated until
+  /// `delay_ms` after `now_ms`.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) {
+    let due = now_ms.wrapping_add(self.delay_ms);
+    if let Some(key) = self.keys.get_mut(event.position()) {
+      match event.edge() {
+        Event::Rising => *key = Some(due),
+        Event::Falling => *key = None,
+        Event::High | Event::Low => {}
+      }
+    }
+  }
+
+  /// Returns the repeated presses due at `now_ms`, one per call.
+  pub fn poll<E: ButtonEvent>(&mut self, now_ms: u32) -> Option<E> {
+    let interval_ms = self.interval_ms;
+    self.keys.iter_mut().find_map(|(position, key)| match key {
+      Some(due) if now_ms.wrapping_sub(*due) as i32 >= 0 => {
+        *due = now_ms.wrapping_add(interval_ms);
+        Some(E::from_parts(position, Event::Rising))
+      }
+      _ => None,
+    })
+  }
+
+  /// Stops repeating all keys, e.g. when the application changes screens.
+  pub fn reset(&mut self) {
+    self.keys.fill(None);
+  }
+}
+
+impl<const W: usize, const H: usize> Default for AutoRepeat<W, H> {
+  /// 500 ms before the first repeat, then 10 repeats a second.
+  fn default() -> Self {
+    Self::new(500, 100)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::AutoRepeat;
+  use crate::input::{parts, press, release};
+  use crate::{Event, MultiEvent};
+
+  #[test]
+  fn repeats_after_the_delay_then_every_interval() {
+    let mut repeat: AutoRepeat = AutoRepeat::new(500, 100);
+    repeat.update(press((2, 1)), 1000);
+    assert!(repeat.poll::<MultiEvent>(1499).is_none());
+    assert_eq!(
+      parts(repeat.poll::<MultiEvent>(1500)),
+      Some(((2, 1), Event::Rising))
+    );
+    assert!(repeat.poll::<MultiEvent>(1599).is_none());
+    assert_eq!(
+      parts(repeat.poll::<MultiEvent>(1600)),
+      Some(((2, 1), Event::Rising))
+    );
+  }
+
+  #[test]
+  fn release_stops_the_repeat() {
+    let mut repeat: AutoRepeat = AutoRepeat::new(500, 100);
+    repeat.update(press((0, 0)), 0);
+    repeat.update(release((0, 0)), 400);
+    assert!(repeat.poll::<MultiEvent>(500).is_none());
+  }
+
+  #[test]
+  fn repeats_across_the_clock_wrapping() {
+    let mut repeat: AutoRepeat = AutoRepeat::new(500, 100);
+    repeat.update(press((0, 0)), u32::MAX - 100);
+    assert!(repeat.poll::<MultiEvent>(u32::MAX).is_none());
+    assert!(repeat.poll::<MultiEvent>(399).is_some());
+  }
+}
@@ -0,0 +1,120 @@
+use super::{ButtonEvent, KeyGrid};
+use crate::{Color, Framebuffer};
+
+/// Latching keys for a `W` x `H` grid of boards: every press flips the state of
+/// the key, as in step sequencers and settings panels.
+///
+/// `draw` mirrors the states to a `Framebuffer` using the on and off colors.
+///
+/// ```ignore
+/// let mut steps: ToggleGrid = ToggleGrid::new(Color::GREEN, Color::BLACK);
+/// let mut frame = Framebuffer::new();
+/// trellis.drain_events(|event| {
+///   steps.update(event);
+/// })?;
+/// steps.draw(&mut frame);
+/// trellis.flush(&frame)?;
+/// ```
+#[derive(Clone)]
+pub struct ToggleGrid<const W: usize = 1, const H: usize = 1> {
+  states: KeyGrid<bool, W, H>,
+  on_color: Color,
+  off_color: Color,
+}
+
+impl<const W: usize, const H: usize> ToggleGrid<W, H> {
+  /// All keys start off.
+  pub const fn new(on_color: Color, off_color: Color) -> Self {
+    Self {
+      states: KeyGrid::new(false),
+      on_color,
+      off_color,
+    }
+  }
+
+  /// Flips the key of a press event, returning its new state.
+  pub fn update<E: ButtonEvent>(&mut self, event: E) -> Option<bool> {
+    if !event.is_press() {
+      return None;
+    }
+    let state = self.states.get_mut(event.position())?;
+    *state = !*state;
+    Some(*state)
+  }
+
+  pub fn get(&self, position: (u8, u8)) -> bool {
+    self.states.get(position).copied().unwrap_or(false)
+  }
+
+  /// Sets the state of the key at `position`, positions outside of the grid are
+  /// ignored.
+  pub fn set(&mut self, position: (u8, u8), on: bool) {
+    if let Some(state) = self.states.get_mut(position) {
+      *state = on;
+    }
+  }
+
+  /// Positions of the keys that are on.
+  pub fn active(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+    self
+      .states
+      .iter()
+      .filter(|(_, on)| **on)
+      .map(|(position, _)| position)
+  }
+
+  pub fn clear(&mut self) {
+    self.states.fill(false);
+  }
+
+  pub fn set_colors(&mut self, on_color: Color, off_color: Color) {
+    self.on_color = on_color;
+    self.off_color = off_color;
+  }
+
+  pub fn colors(&self) -> (Color, Color) {
+    (self.on_color, self.off_color)
+  }
+
+  /// Draws the on color at the keys that are on and the off color elsewhere.
+  pub fn draw(&self, framebuffer: &mut Framebuffer<W, H>) {
+    for ((x, y), on) in self.states.iter() {
+      framebuffer.set(x, y, if *on { self.on_color } else { self.off_color });
+    }
+  }
+}
+
+impl<const W: usize, const H: usize> Default for ToggleGrid<W, H> {
+  /// White when on, black when off.
+  fn default() -> Self {
+    Self::new(Color::WHITE, Color::BLACK)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ToggleGrid;
+  use crate::input::{press, release};
+  use crate::{Color, Framebuffer};
+
+  #[test]
+  fn presses_flip_the_key() {
+    let mut steps: ToggleGrid = ToggleGrid::new(Color::GREEN, Color::BLACK);
+    assert_eq!(steps.update(press((1, 0))), Some(true));
+    assert_eq!(steps.update(release((1, 0))), None);
+    assert_eq!(steps.update(press((2, 3))), Some(true));
+    assert_eq!(steps.update(press((1, 0))), Some(false));
+    assert_eq!(steps.update(press((4, 0))), None);
+    assert!(steps.active().eq([(2, 3)]));
+  }
+
+  #[test]
+  fn draw_shows_the_states() {
+    let mut steps: ToggleGrid = ToggleGrid::new(Color::GREEN, Color::BLACK);
+    let mut frame = Framebuffer::new();
+    steps.set((0, 1), true);
+    steps.draw(&mut frame);
+    assert!(frame.get(0, 1) == Some(Color::GREEN));
+    assert!(frame.get(1, 1) == Some(Color::BLACK));
+  }
+}
@@ -0,0 +1,124 @@
+use super::{ButtonEvent, KeyGrid};
+
+/// A completed key press with a velocity derived from how long the key was held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VelocityEvent {
+  pub position: (u8, u8),
+  /// MIDI style velocity, `1..=127`.
+  pub velocity: u8,
+  pub duration_ms: u32,
+}
+
+/// Emulates velocity for finger drumming: the shorter a key is held, the harder
+/// it was hit.
+///
+/// Presses held for `min_ms` or less get velocity 127, presses held for `max_ms`
+/// or more get velocity 1, durations in between are mapped linearly. The velocity
+/// is only known on release, so that is when the `VelocityEvent` is reported.
+///
+/// ```ignore
+/// let mut velocity: VelocityTracker = VelocityTracker::new(30, 300);
+/// trellis.drain_events(|event| {
+///   if let Some(hit) = velocity.update(event, now_ms()) {
+///     play(hit.position, hit.velocity);
+///   }
+/// })?;
+/// ```
+#[derive(Clone)]
+pub struct VelocityTracker<const W: usize = 1, const H: usize = 1> {
+  min_ms: u32,
+  max_ms: u32,
+  /// Time each held key was pressed at.
+  pressed_at: KeyGrid<Option<u32>, W, H>,
+}
+
+impl<const W: usize, const H: usize> VelocityTracker<W, H> {
+  pub const fn new(min_ms: u32, max_ms: u32) -> Self {
+    Self {
+      min_ms,
+      max_ms,
+      pressed_at: KeyGrid::new(None),
+    }
+  }
+
+  pub fn range(&self) -> (u32, u32) {
+    (self.min_ms, self.max_ms)
+  }
+
+  pub fn set_range(&mut self, min_ms: u32, max_ms: u32) {
+    self.min_ms = min_ms;
+    self.max_ms = max_ms;
+  }
+
+  /// Velocity of a press held for `duration_ms`.
+  pub fn velocity(&self, duration_ms: u32) -> u8 {
+    if duration_ms <= self.min_ms {
+      127
+    } else if duration_ms >= self.max_ms {
+      1
+    } else {
+      let span = u64::from(self.max_ms - self.min_ms);
+      let elapsed = u64::from(duration_ms - self.min_ms);
+      (127 - elapsed * 126 / span) as u8
+    }
+  }
+
+  /// Feeds one event read at `now_ms`, returning the press it completes.
+  pub fn update<E: ButtonEvent>(&mut self, event: E, now_ms: u32) -> Option<VelocityEvent> {
+    let position = event.position();
+    let pressed_at = self.pressed_at.get_mut(position)?;
+
+    if event.is_press() {
+      *pressed_at = Some(now_ms);
+      None
+    } else if event.is_release() {
+      let duration_ms = now_ms.wrapping_sub(pressed_at.take()?);
+      Some(VelocityEvent {
+        position,
+        velocity: self.velocity(duration_ms),
+        duration_ms,
+      })
+    } else {
+      None
+    }
+  }
+}
+
+impl<const W: usize, const H: usize> Default for VelocityTracker<W, H> {
+  /// Full velocity up to 30 ms, the softest from 300 ms.
+  fn default() -> Self {
+    Self::new(30, 300)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{VelocityEvent, VelocityTracker};
+  use crate::input::{press, release};
+
+  #[test]
+  fn velocity_is_mapped_between_the_thresholds() {
+    let velocity: VelocityTracker = VelocityTracker::new(30, 300);
+    assert_eq!(velocity.velocity(0), 127);
+    assert_eq!(velocity.velocity(30), 127);
+    assert_eq!(velocity.velocity(165), 64);
+    assert_eq!(velocity.velocity(299), 2);
+    assert_eq!(velocity.velocity(300), 1);
+    assert_eq!(velocity.velocity(u32::MAX), 1);
+  }
+
+  #[test]
+  fn hit_is_reported_on_release() {
+    let mut velocity: VelocityTracker = VelocityTracker::new(30, 300);
+    assert!(velocity.update(press((3, 0)), 1000).is_none());
+    assert_eq!(
+      velocity.update(release((3, 0)), 1020),
+      Some(VelocityEvent {
+        position: (3, 0),
+        velocity: 127,
+        duration_ms: 20,
+      })
+    );
+    assert!(velocity.update(release((3, 0)), 1030).is_none());
+  }
+}
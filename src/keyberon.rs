@@ -0,0 +1,74 @@
+//! `keyberon` integration, so the keypad can drive keyboard firmware layouts.
+//!
+//! Keyberon addresses keys by `(row, column)`, a key at `(x, y)` becomes `(y, x)`.
+//!
+//! ```ignore
+//! let mut layout = keyberon::layout::Layout::new(LAYERS);
+//! neotrellis::keyberon::scan(&mut trellis, |event| {
+//!   layout.event(event).for_each(drop);
+//! })?;
+//! ```
+
+use ::keyberon::layout::Event as KeyberonEvent;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::input::ButtonEvent;
+use crate::{Error, NeoTrellis, TrellisDevice};
+
+/// Converts a key event to a keyberon event, level events have no equivalent.
+pub fn event<E: ButtonEvent>(event: E) -> Option<KeyberonEvent> {
+  let (x, y) = event.position();
+  if event.is_press() {
+    Some(KeyberonEvent::Press(y, x))
+  } else if event.is_release() {
+    Some(KeyberonEvent::Release(y, x))
+  } else {
+    None
+  }
+}
+
+/// Drains the keypad FIFO of `trellis`, passing the keyberon event of every press
+/// and release to `handler`.
+pub fn scan<I2C, D, F>(
+  trellis: &mut NeoTrellis<I2C, D>,
+  mut handler: F,
+) -> Result<usize, Error<I2C::Error>>
+where
+  I2C: I2c,
+  D: DelayNs,
+  F: FnMut(KeyberonEvent),
+{
+  trellis.drain_events(|e| event(e).into_iter().for_each(&mut handler))
+}
+
+/// Like `scan`, for any `TrellisDevice` such as all boards of a `MultiTrellis`,
+/// using global coordinates.
+pub fn scan_multi<T, F>(trellis: &mut T, mut handler: F) -> Result<usize, T::Error>
+where
+  T: TrellisDevice,
+  F: FnMut(KeyberonEvent),
+{
+  trellis.drain_events(|e| event(e).into_iter().for_each(&mut handler))
+}
+
+#[cfg(test)]
+mod tests {
+  use ::keyberon::layout::Event as KeyberonEvent;
+
+  use crate::{Event, MultiEvent};
+
+  #[test]
+  fn positions_become_row_and_column() {
+    let press = MultiEvent {
+      coordinate: (3, 1),
+      event: Event::Rising,
+    };
+    let held = MultiEvent {
+      coordinate: (3, 1),
+      event: Event::High,
+    };
+    assert_eq!(super::event(press), Some(KeyberonEvent::Press(1, 3)));
+    assert_eq!(super::event(held), None);
+  }
+}
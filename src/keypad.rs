@@ -0,0 +1,170 @@
+use crate::orientation::KeyMap;
+use crate::{Error, Event, EventSet, Key, KeypadEvent};
+
+/// `(key, command)` pairs that fit in one `KEYPAD_EVENT` write.
+pub(crate) const KEY_COMMANDS_PER_WRITE: usize = 15;
+
+pub(crate) const MAX_KEY_COMMAND: usize = 2 * KEY_COMMANDS_PER_WRITE;
+
+/// `KEYPAD_EVENT` command enabling or disabling the events in `events`, one bit
+/// per event.
+pub(crate) const fn key_command(events: u8, enable: bool) -> u8 {
+  events << 1 | enable as u8
+}
+
+/// The `KEYPAD_EVENT` commands setting up the physical `key`, disabling the
+/// events it must not report and then enabling the others.
+pub(crate) fn key_commands(key: Key, events: EventSet, disabled_keys: u16) -> [(Key, u8); 2] {
+  let enabled = if disabled_keys & (1 << key.index()) != 0 {
+    EventSet::NONE
+  } else {
+    events
+  };
+
+  [
+    (
+      key,
+      key_command(EventSet::ALL.bits() & !enabled.bits(), false),
+    ),
+    (key, key_command(enabled.bits(), true)),
+  ]
+}
+
+/// The commands setting up all 16 keys, see `key_commands`.
+pub(crate) fn keypad_commands(events: EventSet, disabled_keys: u16) -> [(Key, u8); 32] {
+  let mut commands = [(Key::from_index(0), 0); 32];
+  for (i, pair) in commands.chunks_exact_mut(2).enumerate() {
+    pair.copy_from_slice(&key_commands(
+      Key::from_index(i as u8),
+      events,
+      disabled_keys,
+    ));
+  }
+
+  commands
+}
+
+/// The `KEYPAD_EVENT` write enabling or disabling `event` for the physical `key`.
+pub(crate) fn key_event_command(key: Key, event: Event, enable: bool) -> [u8; 2] {
+  [
+    key.serialize(),
+    key_command(EventSet::NONE.with(event).bits(), enable),
+  ]
+}
+
+/// The commands of up to `KEY_COMMANDS_PER_WRITE` logical `(key, events, enable)`
+/// entries of `configure_keys`, translated to physical keys.
+pub(crate) fn configure_commands(
+  keymap: &KeyMap,
+  keys: &[(Key, EventSet, bool)],
+) -> [(Key, u8); KEY_COMMANDS_PER_WRITE] {
+  let mut commands = [(Key::from_index(0), 0); KEY_COMMANDS_PER_WRITE];
+  for (command, (key, events, enable)) in commands.iter_mut().zip(keys) {
+    *command = (
+      keymap.physical_key(*key),
+      key_command(events.bits(), *enable),
+    );
+  }
+
+  commands
+}
+
+/// Encodes a `KEYPAD_EVENT` write of up to `KEY_COMMANDS_PER_WRITE`
+/// `(physical key, command)` pairs, returning the length of the write.
+pub(crate) fn encode_key_commands(
+  commands: &[(Key, u8)],
+  value: &mut [u8; MAX_KEY_COMMAND],
+) -> usize {
+  let count = commands.len().min(KEY_COMMANDS_PER_WRITE);
+  for (pair, (key, command)) in value.chunks_exact_mut(2).zip(&commands[..count]) {
+    pair[0] = key.serialize();
+    pair[1] = *command;
+  }
+
+  2 * count
+}
+
+/// Decodes one byte read from the keypad FIFO into an event of the logical key.
+pub(crate) fn decode_event<E>(
+  address: u8,
+  keymap: &KeyMap,
+  wire_byte: u8,
+) -> Result<Option<KeypadEvent>, Error<E>> {
+  Ok(KeypadEvent::decode(address, wire_byte)?.map(|event| keymap.logical_event(event)))
+}
+
+/// Decodes the bytes read from the keypad FIFO, calling `handler` with the event of
+/// every byte in order.
+///
+/// A corrupted byte doesn't stop the decoding, the FIFO has handed the other events
+/// over already. The first one fails with `Error::ProtocolError` once they were all
+/// handled.
+pub(crate) fn decode_fifo<E, F: FnMut(KeypadEvent)>(
+  address: u8,
+  keymap: &KeyMap,
+  bytes: &[u8],
+  mut handler: F,
+) -> Result<(), Error<E>> {
+  let mut result = Ok(());
+  for item in bytes.iter() {
+    match decode_event(address, keymap, *item) {
+      Ok(Some(event)) => handler(event),
+      Ok(None) => {}
+      Err(error) => result = result.and(Err(error)),
+    }
+  }
+
+  result
+}
+
+/// One read of the keypad FIFO, handed out one event at a time by the `events`
+/// readers.
+pub(crate) struct FifoBuffer {
+  bytes: [u8; 32],
+  length: usize,
+  position: usize,
+}
+
+impl FifoBuffer {
+  pub(crate) const fn new() -> Self {
+    Self {
+      bytes: [0u8; 32],
+      length: 0,
+      position: 0,
+    }
+  }
+
+  /// The buffer to read the FIFO into, followed by `set_length`.
+  pub(crate) fn bytes_mut(&mut self) -> &mut [u8; 32] {
+    &mut self.bytes
+  }
+
+  pub(crate) fn set_length(&mut self, length: usize) {
+    self.length = length.min(self.bytes.len());
+    self.position = 0;
+  }
+
+  /// Decodes the next event, a corrupted byte is returned as an error once and
+  /// drops the rest of the read.
+  pub(crate) fn next_event<E>(
+    &mut self,
+    address: u8,
+    keymap: &KeyMap,
+  ) -> Option<Result<KeypadEvent, Error<E>>> {
+    while self.position < self.length {
+      let item = self.bytes[self.position];
+      self.position += 1;
+
+      match decode_event(address, keymap, item) {
+        Ok(Some(event)) => return Some(Ok(event)),
+        Ok(None) => {}
+        Err(e) => {
+          self.position = self.length;
+          return Some(Err(e));
+        }
+      }
+    }
+
+    None
+  }
+}
@@ -0,0 +1,142 @@
+use crate::{Color, Framebuffer, Rotation};
+
+/// Where one board of a `MultiTrellis` sits in the combined coordinate space.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+  /// Grid coordinate of the top left key of the board, as seen by the user.
+  pub origin: (u8, u8),
+  pub rotation: Rotation,
+}
+
+impl Placement {
+  pub const fn new(x: u8, y: u8) -> Self {
+    Placement {
+      origin: (x, y),
+      rotation: Rotation::Deg0,
+    }
+  }
+
+  pub const fn rotated(self, rotation: Rotation) -> Self {
+    Placement { rotation, ..self }
+  }
+
+  /// Grid coordinate of the key at `position` on the board.
+  pub(crate) fn to_grid(self, position: (u8, u8)) -> (u8, u8) {
+    let (x, y) = self.rotation.to_logical(position.0, position.1);
+    (self.origin.0 + x, self.origin.1 + y)
+  }
+
+  fn contains(&self, index: (u8, u8)) -> bool {
+    let (x, y) = index;
+    let (ox, oy) = self.origin;
+
+    x >= ox && x - ox < 4 && y >= oy && y - oy < 4
+  }
+}
+
+/// Placement of every board of a `MultiTrellis`, `placement(x, y)` being the one of
+/// `trellis[x][y]`.
+#[derive(Clone, Copy)]
+pub struct Layout<const W: usize, const H: usize> {
+  placements: [[Placement; H]; W],
+}
+
+impl<const W: usize, const H: usize> Layout<W, H> {
+  /// Row-major rectangle: `trellis[x][y]` covers keys `4 * x..4 * x + 4` and
+  /// `4 * y..4 * y + 4`.
+  pub fn grid() -> Self {
+    Self::from_fn(|x, y| Placement::new(4 * x, 4 * y))
+  }
+
+  /// Zig-zag rows: even rows run left to right like `grid`, odd rows run right to
+  /// left, so `trellis[0][1]` sits below `trellis[W - 1][0]`.
+  pub fn serpentine() -> Self {
+    Self::from_fn(|x, y| {
+      let column = if y % 2 == 0 { x } else { W as u8 - 1 - x };
+      Placement::new(4 * column, 4 * y)
+    })
+  }
+
+  /// Arbitrary layout, e.g. L-shaped panels or boards mounted in other orientations.
+  pub const fn custom(placements: [[Placement; H]; W]) -> Self {
+    Self { placements }
+  }
+
+  pub fn placement(&self, x: usize, y: usize) -> Placement {
+    self.placements[x][y]
+  }
+
+  pub fn set_placement(&mut self, x: usize, y: usize, placement: Placement) {
+    self.placements[x][y] = placement;
+  }
+
+  /// Width and height of the smallest rectangle from `(0, 0)` covering every board.
+  pub fn size(&self) -> (u8, u8) {
+    let mut size = (0, 0);
+    for placement in self.placements.iter().flatten() {
+      let (x, y) = placement.origin;
+      size = (size.0.max(x + 4), size.1.max(y + 4));
+    }
+
+    size
+  }
+
+  /// Maps a grid coordinate to the board holding it and the position on that
+  /// board, rotated as the board is placed.
+  pub(crate) fn locate(&self, index: (u8, u8)) -> Option<(usize, usize, (u8, u8))> {
+    for (x, row) in self.placements.iter().enumerate() {
+      for (y, placement) in row.iter().enumerate() {
+        if placement.contains(index) {
+          let (ox, oy) = placement.origin;
+          let position = placement.rotation.to_physical(index.0 - ox, index.1 - oy);
+          return Some((x, y, position));
+        }
+      }
+    }
+
+    None
+  }
+
+  /// Like `locate`, with the position as an LED or key index of a `NeoTrellis`.
+  pub(crate) fn locate_index(&self, index: (u8, u8)) -> Option<(usize, usize, u8)> {
+    let (x, y, (bx, by)) = self.locate(index)?;
+    Some((x, y, bx + 4 * by))
+  }
+
+  /// Colors of the board `(x, y)` in LED index order, taken from the pixels of
+  /// `framebuffer` it covers. Pixels outside of the framebuffer are black.
+  pub(crate) fn board_colors<const FW: usize, const FH: usize>(
+    &self,
+    x: usize,
+    y: usize,
+    framebuffer: &Framebuffer<FW, FH>,
+  ) -> [Color; 16] {
+    let placement = self.placement(x, y);
+    let mut colors = [Color::BLACK; 16];
+    for (i, color) in colors.iter_mut().enumerate() {
+      let (gx, gy) = placement.to_grid(((i % 4) as u8, (i / 4) as u8));
+      if let Some(c) = framebuffer.get(gx, gy) {
+        *color = c;
+      }
+    }
+
+    colors
+  }
+
+  fn from_fn(f: impl Fn(u8, u8) -> Placement) -> Self {
+    let mut placements = [[Placement::new(0, 0); H]; W];
+    for (x, row) in placements.iter_mut().enumerate() {
+      for (y, placement) in row.iter_mut().enumerate() {
+        *placement = f(x as u8, y as u8);
+      }
+    }
+
+    Self { placements }
+  }
+}
+
+impl<const W: usize, const H: usize> Default for Layout<W, H> {
+  fn default() -> Self {
+    Self::grid()
+  }
+}
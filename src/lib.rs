@@ -1,69 +1,216 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[macro_use]
+mod trace;
+
+mod address;
+mod color;
+mod config;
+mod device;
+mod eeprom;
+mod encoder;
 mod error;
+mod events;
+mod faults;
+mod framebuffer;
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+pub mod input;
+mod keypad;
+mod layout;
+mod neopixel;
+mod orientation;
+mod pixels;
+mod pwm;
+mod retry;
+mod seesaw;
+mod settings;
+mod split;
+mod state;
+mod stats;
+mod status;
+mod touch;
+mod uart;
+
+#[cfg(feature = "async")]
+pub mod asynch;
+#[cfg(feature = "ht16k33")]
+pub mod classic;
+#[cfg(feature = "keyberon")]
+pub mod keyberon;
+#[cfg(feature = "midi")]
+pub mod midi;
+#[cfg(feature = "simulator")]
+pub mod simulator;
 
 use core::convert::TryFrom;
-
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+
+pub use crate::address::Address;
+pub use crate::color::{Color, ColorOrder};
+pub use crate::config::{Config, EventSet};
+pub use crate::device::TrellisDevice;
+pub use crate::encoder::Encoder;
 pub use crate::error::Error;
-use embedded_hal::blocking::delay::{DelayMs, DelayUs};
-use embedded_hal::blocking::i2c::{Read, Write};
+pub use crate::events::{EventConsumer, EventProducer, EventQueue, EventReader};
+pub use crate::faults::BoardStatus;
+use crate::faults::Faults;
+pub use crate::framebuffer::Framebuffer;
+use crate::input::ButtonEvent;
+use crate::keypad::{
+  configure_commands, decode_event, decode_fifo, encode_key_commands, key_commands,
+  key_event_command, keypad_commands, KEY_COMMANDS_PER_WRITE, MAX_KEY_COMMAND,
+};
+pub use crate::layout::{Layout, Placement};
+pub use crate::neopixel::SeesawNeoPixel;
+use crate::orientation::KeyMap;
+pub use crate::orientation::Rotation;
+use crate::pixels::{pixel_writes, PixelBuffer};
+pub use crate::retry::RetryPolicy;
+pub use crate::seesaw::Seesaw;
+pub use crate::settings::Settings;
+use crate::settings::{SETTINGS_LENGTH, SETTINGS_OFFSET};
+pub use crate::split::{KeypadHalf, NeopixelHalf, SharedTrellis};
+pub use crate::state::{
+  HasKeypad, HasNeopixel, KeypadOnly, NeopixelOnly, Ready, State, Uninitialized,
+};
+pub use crate::stats::Stats;
+pub use crate::status::{Capabilities, FirmwareVersion, Temperature};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{Error as _, InputPin};
+use embedded_hal::i2c::I2c;
+use heapless::Vec;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
-use core::convert::TryInto;
-
-#[derive(Clone, Copy)]
-pub struct Color {
-  pub r: u8,
-  pub g: u8,
-  pub b: u8,
-}
-
-impl Color {
-  pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
-    Color { r, g, b }
-  }
-
-  pub const fn as_grb_slice(&self) -> [u8; 3] {
-    [self.g, self.r, self.b]
-  }
-}
-
 #[repr(u8)]
-#[derive(TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[derive(TryFromPrimitive, IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Event {
   High = 0,
   Low = 1,
   Falling = 2,
   Rising = 3,
 }
+/// Seesaw chips, as reported by the hardware ID register.
+///
+/// The original NeoTrellis is built around a SAMD09, newer seesaw boards around
+/// ATtiny8x6/8x7 chips.
+#[repr(u8)]
+#[derive(TryFromPrimitive, IntoPrimitive, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HardwareId {
+  Samd09 = 0x55,
+  Attiny806 = 0x84,
+  Attiny807 = 0x85,
+  Attiny816 = 0x86,
+  Attiny817 = 0x87,
+  Attiny1616 = 0x88,
+  Attiny1617 = 0x89,
+}
+
+/// Data rate of the neopixel output.
+#[repr(u8)]
+#[derive(IntoPrimitive, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+  Khz400 = 0,
+  Khz800 = 1,
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeypadEvent {
   pub key: Key,
   pub event: Event,
 }
 
+impl KeypadEvent {
+  /// Decodes one byte read from the keypad FIFO, `0xff` marks an empty slot.
+  ///
+  /// The seesaw numbers the keys of row `r` and column `c` as `8 * r + c`, any other
+  /// key number is a corrupted read and fails with `Error::ProtocolError`.
+  pub(crate) fn decode<E>(address: u8, wire_byte: u8) -> Result<Option<Self>, Error<E>> {
+    if wire_byte == 0xff {
+      return Ok(None);
+    }
+
+    let key_number = wire_byte >> 2;
+    if key_number & 0x04 != 0 || key_number >= 32 {
+      return Err(Error::ProtocolError {
+        address,
+        byte: wire_byte,
+      });
+    }
+    let event = Event::try_from(wire_byte & 0x03).map_err(|_| Error::ProtocolError {
+      address,
+      byte: wire_byte,
+    })?;
+
+    Ok(Some(KeypadEvent {
+      key: Key::deserialize(key_number),
+      event,
+    }))
+  }
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiEvent {
   pub coordinate: (u8, u8),
   pub event: Event,
 }
 
-pub struct MultiTrellis<'a, I2C>
-where
-  I2C: Write + Read,
-{
-  pub trellis: &'a mut [&'a mut [NeoTrellis<I2C>]],
+impl MultiEvent {
+  /// Translates an event in the coordinates of the board at `placement` to global
+  /// coordinates.
+  pub(crate) fn from_placement<E: ButtonEvent>(placement: Placement, event: E) -> Self {
+    MultiEvent {
+      coordinate: placement.to_grid(event.position()),
+      event: event.edge(),
+    }
+  }
 }
 
-pub struct NeoTrellis<I2C>
+/// A `W` x `H` array of boards addressed as one continuous coordinate space, for
+/// any `TrellisDevice` of 4x4 keys such as a `NeoTrellis` or a mock.
+///
+/// By default `trellis[x][y]` is the board covering keys `4 * x..4 * x + 4`
+/// horizontally and `4 * y..4 * y + 4` vertically, a `Layout` can place the boards
+/// differently.
+pub struct TrellisGrid<T, const W: usize, const H: usize> {
+  pub trellis: [[T; H]; W],
+  layout: Layout<W, H>,
+  faults: Faults<W, H>,
+}
+
+/// A grid of `NeoTrellis` boards, with the board settings and keypad methods on
+/// top of those of every `TrellisGrid`.
+pub type MultiTrellis<I2C, D, const W: usize, const H: usize> =
+  TrellisGrid<NeoTrellis<I2C, D>, W, H>;
+
+pub struct NeoTrellis<I2C, D, S = Ready>
 where
-  I2C: Write + Read,
+  I2C: I2c,
+  D: DelayNs,
 {
-  bus: I2C,
-  address: u8,
+  seesaw: Seesaw<I2C, D>,
+  neopixel_pin: u8,
+  pixels: PixelBuffer,
+  keymap: KeyMap,
+  speed: Speed,
+  interrupt: bool,
+  auto_recover: Option<u8>,
+  key_events: EventSet,
+  /// Physical keys that report no events.
+  disabled_keys: u16,
+  state: PhantomData<S>,
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Key(u8);
 
 impl Key {
@@ -84,140 +231,494 @@ impl Key {
   }
 }
 
+/// Prints the key index and its `(x, y)` position, e.g. `key 6 (2, 1)`.
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Key {
+  fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error> {
+    ufmt::uwrite!(f, "key {} ({}, {})", self.0, self.0 % 4, self.0 / 4)
+  }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Event {
+  fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error> {
+    f.write_str(match self {
+      Event::High => "high",
+      Event::Low => "low",
+      Event::Falling => "falling",
+      Event::Rising => "rising",
+    })
+  }
+}
+
 #[repr(u8)]
-#[derive(IntoPrimitive)]
-enum Module {
+#[derive(IntoPrimitive, Clone, Copy)]
+pub(crate) enum Module {
   Status = 0x00,
+  Gpio = 0x01,
+  Sercom0 = 0x02,
+  Timer = 0x08,
+  Eeprom = 0x0D,
   Neopixel = 0x0E,
+  Touch = 0x0F,
   Keypad = 0x10,
+  Encoder = 0x11,
 }
 
-const STATUS_HW_ID: u8 = 0x01;
-const STATUS_SWRST: u8 = 0x7f;
+pub(crate) const STATUS_HW_ID: u8 = 0x01;
+pub(crate) const STATUS_VERSION: u8 = 0x02;
+pub(crate) const STATUS_OPTIONS: u8 = 0x03;
+pub(crate) const STATUS_TEMP: u8 = 0x04;
+pub(crate) const STATUS_SWRST: u8 = 0x7f;
 
-const NEOPIXEL_PIN: u8 = 0x01;
-const _NEOPIXEL_SPEED: u8 = 0x02;
-const NEOPIXEL_BUF_LENGTH: u8 = 0x03;
-const NEOPIXEL_BUF: u8 = 0x04;
-const NEOPIXEL_SHOW: u8 = 0x05;
+pub(crate) const NEOPIXEL_PIN: u8 = 0x01;
+pub(crate) const NEOPIXEL_SPEED: u8 = 0x02;
+pub(crate) const NEOPIXEL_BUF_LENGTH: u8 = 0x03;
+pub(crate) const NEOPIXEL_BUF: u8 = 0x04;
+pub(crate) const NEOPIXEL_SHOW: u8 = 0x05;
 
 const _KEYPAD_STATUS: u8 = 0x00;
-const KEYPAD_EVENT: u8 = 0x01;
-const _KEYPAD_INTENSET: u8 = 0x02;
-const _KEYPAD_INTENCLR: u8 = 0x03;
-const KEYPAD_COUNT: u8 = 0x04;
-const KEYPAD_FIFO: u8 = 0x10;
+pub(crate) const KEYPAD_EVENT: u8 = 0x01;
+pub(crate) const KEYPAD_INTENSET: u8 = 0x02;
+pub(crate) const KEYPAD_INTENCLR: u8 = 0x03;
+pub(crate) const KEYPAD_COUNT: u8 = 0x04;
+pub(crate) const KEYPAD_FIFO: u8 = 0x10;
+
+/// Interval at which the hardware ID is polled after a reset.
+pub(crate) const RESET_POLL_MS: u32 = 10;
 
-const HW_ID_CODE: u8 = 0x55;
+/// Seesaw pin wired to the pixels on the NeoTrellis board.
+pub const NEOTRELLIS_NEOPIXEL_PIN: u8 = 3;
+/// Addresses a NeoTrellis can be set to with its address jumpers, 0x2E without any.
+pub const NEOTRELLIS_ADDRESSES: RangeInclusive<u8> = 0x2E..=0x3D;
 
-impl<'a, I2> MultiTrellis<'a, I2>
+impl<T, E, const W: usize, const H: usize> TrellisGrid<T, W, H>
 where
-  I2: Read + Write,
-  <I2 as Read>::Error: core::fmt::Debug,
-  <I2 as Write>::Error: core::fmt::Debug,
+  T: TrellisDevice<Error = Error<E>>,
 {
-  pub fn set_led_color<DELAY: DelayMs<u32> + DelayUs<u32>>(
-    &mut self,
-    index: (u8, u8),
-    color: Color,
-    delay: &mut DELAY,
-  ) -> Result<(), Error<I2>> {
-    let (x, y) = index;
+  /// Places `trellis[x][y]` at `(4 * x, 4 * y)`, see `Layout::grid`.
+  pub fn new(trellis: [[T; H]; W]) -> Self {
+    Self::with_layout(trellis, Layout::grid())
+  }
+
+  /// Panics if a board doesn't have 4x4 keys.
+  pub fn with_layout(trellis: [[T; H]; W], layout: Layout<W, H>) -> Self {
+    assert!(
+      trellis.iter().flatten().all(|board| board.size() == (4, 4)),
+      "boards of a grid have 4x4 keys"
+    );
+
+    Self {
+      trellis,
+      layout,
+      faults: Faults::new(),
+    }
+  }
+
+  /// Changes where the boards sit. The grid rotates the coordinates of each board
+  /// as its placement says, on top of any rotation set on the board itself.
+  pub fn set_layout(&mut self, layout: Layout<W, H>) {
+    self.layout = layout;
+  }
+
+  pub fn layout(&self) -> &Layout<W, H> {
+    &self.layout
+  }
+
+  pub fn into_inner(self) -> [[T; H]; W] {
+    self.trellis
+  }
+
+  /// In fault tolerant mode a board that fails is marked as faulted and skipped
+  /// from then on, instead of failing the operation on the whole grid. Off by
+  /// default.
+  pub fn set_fault_tolerant(&mut self, enable: bool) {
+    self.faults.set_tolerant(enable);
+  }
+
+  pub fn fault_tolerant(&self) -> bool {
+    self.faults.tolerant()
+  }
+
+  /// Status of `trellis[x][y]` after the last operation on it.
+  pub fn board_status(&self, x: usize, y: usize) -> BoardStatus {
+    self.faults.status(x, y)
+  }
+
+  /// Grid positions of the faulted boards.
+  pub fn faulted_boards(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.faults.faulted()
+  }
+
+  /// Marks all boards as healthy again, so fault tolerant mode stops skipping them.
+  pub fn clear_faults(&mut self) {
+    self.faults.clear();
+  }
+
+  /// Sets the color of the LED at `index`, fails with `Error::OutOfBounds` if no
+  /// board covers `index`.
+  pub fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), Error<E>> {
+    let (tx, ty, position) = self.layout.locate(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].set_led_color(position, color)
+  }
+
+  pub fn show(&mut self) -> Result<(), Error<E>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.show())?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Sets every LED of every board to `color`, applied by the next `show`.
+  pub fn fill(&mut self, color: Color) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.fill(color);
+      }
+    }
+  }
+
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Fills every board and shows it right away, e.g. `fill_and_show(Color::BLACK)`
+  /// to turn the whole grid off from an error handler.
+  pub fn fill_and_show(&mut self, color: Color) -> Result<(), Error<E>> {
+    self.fill(color);
+    self.show()
+  }
+
+  /// Drains the key events of every board, calling `handler` with each event in
+  /// grid coordinates. Returns the number of events handled.
+  pub fn drain_events<F: FnMut(MultiEvent)>(&mut self, mut handler: F) -> Result<usize, Error<E>> {
+    let mut handled = 0;
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if self.faults.skip(x, y) {
+          continue;
+        }
+        let placement = self.layout.placement(x, y);
+        let result = trellis.drain_events(|e| handler(MultiEvent::from_placement(placement, e)));
+        handled += self.faults.check(x, y, result)?.unwrap_or(0);
+      }
+    }
+
+    Ok(handled)
+  }
+}
+
+impl<I2, D, const W: usize, const H: usize> MultiTrellis<I2, D, W, H>
+where
+  I2: I2c,
+  D: DelayNs,
+{
+  /// Checks which boards respond, for setups where boards are plugged in and out.
+  ///
+  /// A faulted board that responds again is recovered, see `NeoTrellis::recover`,
+  /// and becomes healthy. A healthy board that doesn't respond is marked as
+  /// faulted. `handler` is called with the position and new status of every board
+  /// that changed, the number of changes is returned.
+  pub fn rescan<F: FnMut(usize, usize, BoardStatus)>(&mut self, mut handler: F) -> usize {
+    let mut changes = 0;
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        let present = trellis.is_present();
+        let status = match self.faults.status(x, y) {
+          BoardStatus::Faulted if present && trellis.recover(true).is_ok() => BoardStatus::Ok,
+          BoardStatus::Ok if !present => BoardStatus::Faulted,
+          _ => continue,
+        };
+
+        self.faults.set(x, y, status);
+        handler(x, y, status);
+        changes += 1;
+      }
+    }
+
+    changes
+  }
+
+  /// Sets the automatic recovery of every board, see `NeoTrellis::set_auto_recover`.
+  pub fn set_auto_recover(&mut self, errors: Option<u8>) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_auto_recover(errors);
+      }
+    }
+  }
+
+  /// Bus traffic of all boards together, see `NeoTrellis::stats`.
+  pub fn stats(&self) -> Stats {
+    self
+      .trellis
+      .iter()
+      .flatten()
+      .fold(Stats::new(), |stats, trellis| stats + trellis.stats())
+  }
+
+  pub fn reset_stats(&mut self) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.reset_stats();
+      }
+    }
+  }
 
-    let tx = usize::from(x / 4);
-    let ty = usize::from(y / 4);
+  /// Sets the retry policy of every board, see `NeoTrellis::set_retry_policy`.
+  pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_retry_policy(policy);
+      }
+    }
+  }
 
-    let i = x % 4 + (y % 4) * 4;
+  /// Sets the brightness of every board, see `NeoTrellis::set_brightness`.
+  pub fn set_brightness(&mut self, brightness: u8) {
+    for row in self.trellis.iter_mut() {
+      for trellis in row.iter_mut() {
+        trellis.set_brightness(brightness);
+      }
+    }
+  }
 
-    if tx < self.trellis.len() && ty < self.trellis[tx].len() {
-      self.trellis[tx][ty].set_led_color(i, color, delay)?;
+  /// Sets the color order of every board, see `NeoTrellis::set_color_order`.
+  pub fn set_color_order(&mut self, order: ColorOrder) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.set_color_order(order))?;
+        }
+      }
     }
 
     Ok(())
   }
 
-  pub fn show<DELAY: DelayUs<u32>>(&mut self, delay: &mut DELAY) -> Result<(), Error<I2>> {
+  /// Enables gamma correction on every board, see `NeoTrellis::set_gamma_correction`.
+  pub fn set_gamma_correction(&mut self, enable: bool) {
     for row in self.trellis.iter_mut() {
       for trellis in row.iter_mut() {
-        trellis.show_led(delay)?
+        trellis.set_gamma_correction(enable);
+      }
+    }
+  }
+
+  /// Sets the brightness of the LED at `index`, see `NeoTrellis::set_pixel_brightness`.
+  /// Fails with `Error::OutOfBounds` if no board covers `index`.
+  pub fn set_pixel_brightness(
+    &mut self,
+    index: (u8, u8),
+    brightness: u8,
+  ) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].set_pixel_brightness(i, brightness)
+  }
+
+  /// Writes the framebuffer to every board, then shows all of them. Each board
+  /// takes the pixels covered by its placement, pixels outside of the framebuffer
+  /// are turned off.
+  pub fn flush<const FW: usize, const FH: usize>(
+    &mut self,
+    framebuffer: &Framebuffer<FW, FH>,
+  ) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        trellis.set_led_colors(&self.layout.board_colors(x, y, framebuffer))?;
+      }
+    }
+
+    self.show()
+  }
+
+  /// Stops the key at `index` from reporting any event, see
+  /// `NeoTrellis::disable_key`.
+  pub fn disable_key(&mut self, index: (u8, u8)) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].disable_key(Key::from_index(i))
+  }
+
+  pub fn enable_key(&mut self, index: (u8, u8)) -> Result<(), Error<I2::Error>> {
+    let (tx, ty, i) = self.layout.locate_index(index).ok_or(Error::OutOfBounds)?;
+    self.trellis[tx][ty].enable_key(Key::from_index(i))
+  }
+
+  pub fn key_enabled(&self, index: (u8, u8)) -> bool {
+    match self.layout.locate_index(index) {
+      Some((tx, ty, i)) => self.trellis[tx][ty].key_enabled(Key::from_index(i)),
+      None => false,
+    }
+  }
+
+  /// Makes every key of every board report exactly `events`, see
+  /// `NeoTrellis::set_key_events`.
+  pub fn set_key_events(&mut self, events: EventSet) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.set_key_events(events))?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Enables the keypad interrupt of every board, their open-drain INT lines can
+  /// be wired together to a single input.
+  pub fn enable_interrupt(&mut self) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.enable_interrupt())?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn disable_interrupt(&mut self) -> Result<(), Error<I2::Error>> {
+    for (x, row) in self.trellis.iter_mut().enumerate() {
+      for (y, trellis) in row.iter_mut().enumerate() {
+        if !self.faults.skip(x, y) {
+          self.faults.check(x, y, trellis.disable_interrupt())?;
+        }
       }
     }
 
     Ok(())
   }
 
-  pub fn read_events<DELAY: DelayMs<u32>>(
+  /// Reads the pending events of all boards into `events`, one board after the
+  /// other, in global coordinates.
+  ///
+  /// The events are stored at the start of `events` and the remaining slots are set
+  /// to `None`. Events that don't fit stay in the FIFO of their board for the next
+  /// call. Returns the number of events read.
+  pub fn read_events(
     &mut self,
     events: &mut [Option<MultiEvent>],
-    delay: &mut DELAY,
-  ) -> Result<(), Error<I2>> {
-    
+  ) -> Result<usize, Error<I2::Error>> {
+    let mut count = 0;
     for (x, row) in self.trellis.iter_mut().enumerate() {
       for (y, trellis) in row.iter_mut().enumerate() {
-        let mut single_event = [None; 16];
-        trellis.read_key_events(&mut single_event, delay)?;
-
-        for e in single_event {
-          let xc: u8= x.try_into().unwrap();
-          let yc: u8 = y.try_into().unwrap();
-          match e {
-            Some(KeypadEvent { key, event }) => {
-              events[x + 4 * y] = Some(MultiEvent {
-                coordinate: (4 * xc + key.index() % 4, 4 * yc + key.index() / 4),
-                event
-              })
-            },
-            _ => ()
-          }
+        let room = (events.len() - count).min(32);
+        if room == 0 {
+          break;
+        }
+
+        if self.faults.skip(x, y) {
+          continue;
+        }
+
+        let mut board_events = [None; 32];
+        let result = trellis.read_key_events(&mut board_events[..room]);
+        let read = self.faults.check(x, y, result)?.unwrap_or(0);
+
+        let placement = self.layout.placement(x, y);
+        for (slot, e) in events[count..]
+          .iter_mut()
+          .zip(board_events[..read].iter().flatten())
+        {
+          *slot = Some(MultiEvent::from_placement(placement, *e));
         }
+        count += read;
       }
     }
+    for event in events[count..].iter_mut() {
+      *event = None;
+    }
 
-    Ok(())
+    Ok(count)
   }
 }
 
-impl<I2C> NeoTrellis<I2C>
+impl<I2C, D> NeoTrellis<I2C, D, Uninitialized>
 where
-  I2C: Read + Write,
-  <I2C as Read>::Error: core::fmt::Debug,
-  <I2C as Write>::Error: core::fmt::Debug,
+  I2C: I2c,
+  D: DelayNs,
 {
-  pub fn new<DELAY: DelayMs<u32>>(
-    bus: I2C,
-    address: u8,
-    delay: &mut DELAY,
-  ) -> Result<Self, Error<I2C>> {
-    let mut neotrellis = Self { bus, address };
-
-    neotrellis.soft_reset(delay)?;
-    neotrellis.setup_neopixel()?;
-    neotrellis.setup_keypad()?;
+  /// Creates the driver without any bus traffic, `init` then resets and configures
+  /// the board. The reset behavior and the retry policy can be set up in between.
+  pub fn uninitialized(bus: I2C, address: Address, delay: D) -> Self {
+    Self {
+      seesaw: Seesaw::new(bus, address, delay),
+      neopixel_pin: NEOTRELLIS_NEOPIXEL_PIN,
+      pixels: PixelBuffer::new(),
+      keymap: KeyMap::new(),
+      speed: Speed::Khz800,
+      interrupt: false,
+      auto_recover: None,
+      key_events: EventSet::EDGES,
+      disabled_keys: 0,
+      state: PhantomData,
+    }
+  }
 
-    Ok(neotrellis)
+  /// Sets the events the keypad reports for every key once `init` or `init_keypad`
+  /// set it up, rising and falling edges by default.
+  pub fn with_key_events(mut self, events: EventSet) -> Self {
+    self.key_events = events;
+    self
   }
 
-  fn soft_reset<DELAY: DelayMs<u32>>(&mut self, delay: &mut DELAY) -> Result<(), Error<I2C>> {
-    self.write_register(Module::Status, STATUS_SWRST, &[0xff])?;
-    delay.delay_ms(500);
+  /// Resets and configures the board, the driver is ready to use afterwards.
+  pub fn init(mut self) -> Result<NeoTrellis<I2C, D>, Error<I2C::Error>> {
+    self.soft_reset()?;
+    self.setup_neopixel()?;
+    self.setup_keypad()?;
 
-    let mut id = [0u8];
-    self.read_register(Module::Status, STATUS_HW_ID, &mut id, delay)?;
+    Ok(self.into_state())
+  }
 
-    if id[0] != HW_ID_CODE {
-      Err(Error::WrongChipId)
-    } else {
-      Ok(())
-    }
+  /// Resets the board and sets up only the keypad, the LEDs stay off.
+  pub fn init_keypad(mut self) -> Result<NeoTrellis<I2C, D, KeypadOnly>, Error<I2C::Error>> {
+    self.soft_reset()?;
+    self.setup_keypad()?;
+
+    Ok(self.into_state())
   }
 
-  fn setup_neopixel(&mut self) -> Result<(), Error<I2C>> {
+  /// Resets the board and sets up only the neopixel module, the keypad reports
+  /// nothing.
+  pub fn init_neopixel(mut self) -> Result<NeoTrellis<I2C, D, NeopixelOnly>, Error<I2C::Error>> {
+    self.soft_reset()?;
+    self.setup_neopixel()?;
+
+    Ok(self.into_state())
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: State,
+{
+  /// Resets the seesaw, then polls its hardware ID until the board answers or the
+  /// reset timeout is over.
+  ///
+  /// The board comes back with the LEDs off and the keypad reporting nothing. The
+  /// driver keeps its settings and cached LED colors, `reinit_peripherals` applies
+  /// them to the board again.
+  pub fn soft_reset(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.soft_reset()
+  }
+
+  fn setup_neopixel(&mut self) -> Result<(), Error<I2C::Error>> {
     // Set the neopixel pin
-    let pin: u8 = 3;
+    let pin = self.neopixel_pin;
     self.write_register(Module::Neopixel, NEOPIXEL_PIN, &pin.to_be_bytes())?;
 
-    // We have 16 LEDs * 3 colors
-    let buffer_length: u16 = 16 * 3;
+    // We have 16 LEDs * 3 or 4 bytes depending on the color order
+    let buffer_length = self.pixels.buffer_length();
     self.write_register(
       Module::Neopixel,
       NEOPIXEL_BUF_LENGTH,
@@ -227,126 +728,806 @@ where
     Ok(())
   }
 
-  fn setup_keypad(&mut self) -> Result<(), Error<I2C>> {
-    // Enable only rising and falling edge detections for all 16 keys
-    for i in 0..16 {
-      let key = Key::from_index(i);
-      self.set_key_event(key, Event::Low, false)?;
-      self.set_key_event(key, Event::High, false)?;
-      self.set_key_event(key, Event::Falling, true)?;
-      self.set_key_event(key, Event::Rising, true)?;
+  fn setup_keypad(&mut self) -> Result<(), Error<I2C::Error>> {
+    // Enable only the configured events for all 16 keys
+    let commands = keypad_commands(self.key_events, self.disabled_keys);
+
+    self.write_key_commands(&commands)
+  }
+
+  /// Enables the configured events of the physical `key`, or none if it is disabled.
+  fn setup_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    let commands = key_commands(key, self.key_events, self.disabled_keys);
+    self.write_key_commands(&commands)
+  }
+
+  /// Writes `(physical key, command)` pairs, as many as fit into each
+  /// `KEYPAD_EVENT` write.
+  fn write_key_commands(&mut self, commands: &[(Key, u8)]) -> Result<(), Error<I2C::Error>> {
+    for chunk in commands.chunks(KEY_COMMANDS_PER_WRITE) {
+      let mut value = [0u8; MAX_KEY_COMMAND];
+      let length = encode_key_commands(chunk, &mut value);
+      self.write_register(Module::Keypad, KEYPAD_EVENT, &value[..length])?;
     }
 
     Ok(())
   }
 
-  pub fn set_key_event(&mut self, key: Key, event: Event, enable: bool) -> Result<(), Error<I2C>> {
-    let command = (1 << (u8::from(event) + 1)) | (enable as u8);
-    self.write_register(Module::Keypad, KEYPAD_EVENT, &[key.serialize(), command])?;
+  /// Enables or disables `event` for the physical `key`.
+  fn write_key_event(
+    &mut self,
+    key: Key,
+    event: Event,
+    enable: bool,
+  ) -> Result<(), Error<I2C::Error>> {
+    let command = key_event_command(key, event, enable);
+    self.write_register(Module::Keypad, KEYPAD_EVENT, &command)?;
 
     Ok(())
   }
 
-  fn read_register<DELAY: DelayMs<u32>>(
+  /// Reads a register, retrying as the retry policy says.
+  fn read_register(
     &mut self,
-    module: Module,
+    module: impl Into<u8>,
     register: u8,
     value: &mut [u8],
-    delay: &mut DELAY,
-  ) -> Result<(), Error<I2C>> {
-    let command = [module.into(), register];
-    self
-      .bus
-      .write(self.address, &command[0..2])
-      .map_err(|e| Error::WriteError(e))?;
-
-    delay.delay_ms(6u32);
+  ) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.read(module.into(), register, value)
+  }
 
-    self
-      .bus
-      .read(self.address, value)
-      .map_err(|e| Error::ReadError(e))?;
+  /// Writes a register, retrying as the retry policy says.
+  fn write_register(
+    &mut self,
+    module: impl Into<u8>,
+    register: u8,
+    value: &[u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.write(module.into(), register, value)
+  }
 
-    Ok(())
+  /// Reads `value.len()` bytes from `register` of the seesaw `module`, for the
+  /// registers this driver doesn't model. Retried like every other read.
+  pub fn read_raw(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &mut [u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.read_register(module, register, value)
   }
 
-  fn write_register(
+  /// Writes up to 31 bytes to `register` of the seesaw `module`, longer values fail
+  /// with `Error::OutOfBounds`.
+  ///
+  /// The driver doesn't know what the write changes, e.g. writing the pixel buffer
+  /// leaves the cached LED colors out of date.
+  pub fn write_raw(
     &mut self,
-    module: Module,
+    module: u8,
     register: u8,
     value: &[u8],
-  ) -> Result<(), Error<I2C>> {
-    assert!(value.len() < 32);
-    let mut command = [0u8; 34];
-    command[0] = module.into();
-    command[1] = register;
-    command[2..(2 + value.len())].copy_from_slice(value);
-    self
-      .bus
-      .write(self.address, &command[0..(2 + value.len())])
-      .map_err(|e| Error::WriteError(e))?;
+  ) -> Result<(), Error<I2C::Error>> {
+    self.write_register(module, register, value)
+  }
+
+  /// Whether the board answers with the seesaw hardware ID, without resetting it.
+  pub(crate) fn is_present(&mut self) -> bool {
+    self.seesaw.is_present()
+  }
+
+  /// Reads which seesaw chip the board is built around.
+  pub fn hardware_id(&mut self) -> Result<HardwareId, Error<I2C::Error>> {
+    self.seesaw.hardware_id()
+  }
+
+  /// Reads the product ID and build date of the seesaw firmware.
+  pub fn firmware_version(&mut self) -> Result<FirmwareVersion, Error<I2C::Error>> {
+    self.seesaw.firmware_version()
+  }
+
+  /// Reads which seesaw modules the firmware implements, for generic seesaw
+  /// boards that may lack the keypad or the neopixel module.
+  pub fn capabilities(&mut self) -> Result<Capabilities, Error<I2C::Error>> {
+    self.seesaw.capabilities()
+  }
+
+  /// Reads the temperature of the seesaw chip.
+  pub fn temperature(&mut self) -> Result<Temperature, Error<I2C::Error>> {
+    self.seesaw.temperature()
+  }
+
+  /// Gives back the bus and the delay, e.g. to hand the bus to another driver.
+  pub fn free(self) -> (I2C, D) {
+    self.seesaw.free()
+  }
+
+  pub fn address(&self) -> Address {
+    self.seesaw.address()
+  }
+
+  /// Changes the I2C address of the board persistently, see `Seesaw::set_address`.
+  pub fn set_address(&mut self, address: Address) -> Result<(), Error<I2C::Error>> {
+    self.seesaw.set_address(address)
+  }
+
+  /// The seesaw transport, e.g. for the modules of custom seesaw firmware this
+  /// driver doesn't use.
+  pub fn seesaw(&mut self) -> &mut Seesaw<I2C, D> {
+    &mut self.seesaw
+  }
+
+  /// Sets how long a reset, e.g. by `recover`, waits for the board to come back
+  /// before failing. The board is polled, so the reset finishes as soon as it
+  /// answers. 500 ms by default.
+  pub fn set_reset_timeout(&mut self, timeout_ms: u32) {
+    self.seesaw.set_reset_timeout(timeout_ms);
+  }
+
+  pub fn reset_timeout(&self) -> u32 {
+    self.seesaw.reset_timeout()
+  }
+
+  /// Sets how failed register reads and writes are retried, by default they aren't.
+  pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+    self.seesaw.set_retry_policy(policy);
+  }
+
+  pub fn retry_policy(&self) -> RetryPolicy {
+    self.seesaw.retry_policy()
+  }
+
+  /// Bus traffic of this board, e.g. to measure what a frame or a poll costs.
+  pub fn stats(&self) -> Stats {
+    self.seesaw.stats()
+  }
+
+  pub fn reset_stats(&mut self) {
+    self.seesaw.reset_stats();
+  }
+
+  /// Events the keypad reports for every key after a setup, see `set_key_events`.
+  pub fn key_events(&self) -> EventSet {
+    self.key_events
+  }
+
+  fn into_state<T>(self) -> NeoTrellis<I2C, D, T> {
+    NeoTrellis {
+      seesaw: self.seesaw,
+      neopixel_pin: self.neopixel_pin,
+      pixels: self.pixels,
+      keymap: self.keymap,
+      speed: self.speed,
+      interrupt: self.interrupt,
+      auto_recover: self.auto_recover,
+      key_events: self.key_events,
+      disabled_keys: self.disabled_keys,
+      state: PhantomData,
+    }
+  }
+
+  /// Resets the board and sets it up again, e.g. after an ESD event locked up
+  /// the seesaw.
+  ///
+  /// Like `reinit_peripherals` after the reset. Without `restore_pixels` the cached
+  /// LED colors are dropped and the LEDs stay off.
+  pub fn recover(&mut self, restore_pixels: bool) -> Result<(), Error<I2C::Error>> {
+    self.soft_reset()?;
+    if !restore_pixels {
+      self.pixels.reset();
+    }
+
+    self.reinit_peripherals()
+  }
+
+  /// Sets up the modules of the state again without a reset, e.g. after a
+  /// brown-out of the LED supply.
+  ///
+  /// The neopixel pin, speed and color order and the keypad interrupt are restored,
+  /// the keypad reports the configured events of all keys again and the
+  /// cached LED colors are shown again.
+  pub fn reinit_peripherals(&mut self) -> Result<(), Error<I2C::Error>> {
+    if S::NEOPIXEL {
+      self.setup_neopixel()?;
+      let speed = self.speed;
+      self.write_register(Module::Neopixel, NEOPIXEL_SPEED, &[speed.into()])?;
+    }
+    if S::KEYPAD {
+      self.setup_keypad()?;
+      if self.interrupt {
+        self.write_register(Module::Keypad, KEYPAD_INTENSET, &[0x01])?;
+      }
+    }
+
+    if S::NEOPIXEL {
+      self.pixels.mark_dirty();
+      self.show_pixels()?;
+    }
 
     Ok(())
   }
 
-  pub fn set_led_color<DELAY: DelayUs<u32>>(
-    &mut self,
-    led: u8,
-    color: Color,
-    delay: &mut DELAY,
-  ) -> Result<(), Error<I2C>> {
-    let led_address = (led as u16) * 3;
-    let mut command = [0u8; 5];
+  /// Makes `show_led` and the keypad reads call `recover(true)` first once
+  /// `errors` register accesses failed in a row, `None` turns it off (the default).
+  pub fn set_auto_recover(&mut self, errors: Option<u8>) {
+    self.auto_recover = errors;
+  }
+
+  pub fn auto_recover(&self) -> Option<u8> {
+    self.auto_recover
+  }
+
+  fn recover_if_needed(&mut self) -> Result<(), Error<I2C::Error>> {
+    match self.auto_recover {
+      Some(errors) if self.seesaw.errors() >= errors => self.recover(true),
+      _ => Ok(()),
+    }
+  }
+
+  /// Rotates the board clockwise: LED indices and key events are translated so the
+  /// application keeps working in the orientation the board is looked at.
+  pub fn set_rotation(&mut self, rotation: Rotation) {
+    self.keymap.set_rotation(rotation);
+  }
+
+  pub fn rotation(&self) -> Rotation {
+    self.keymap.rotation()
+  }
+
+  /// Mirrors the key and LED coordinates horizontally and/or vertically, e.g. for a
+  /// board seen from behind a panel. Applied on top of the rotation.
+  pub fn set_mirroring(&mut self, mirror_x: bool, mirror_y: bool) {
+    self.keymap.set_mirroring(mirror_x, mirror_y);
+  }
 
-    command[0..2].copy_from_slice(&led_address.to_be_bytes());
-    command[2..5].copy_from_slice(&color.as_grb_slice());
+  pub fn mirroring(&self) -> (bool, bool) {
+    self.keymap.mirroring()
+  }
+
+  /// Installs a remap table where `remap[logical]` is the key a logical key index
+  /// stands for, so custom panel labels are translated once for LEDs and events.
+  ///
+  /// The table is applied before mirroring and rotation. Panics if `remap` is not a
+  /// permutation of the 16 keys.
+  pub fn set_key_remap(&mut self, remap: [u8; 16]) {
+    self.keymap.set_remap(remap);
+  }
 
-    self.write_register(Module::Neopixel, NEOPIXEL_BUF, &command)?;
+  pub fn key_remap(&self) -> &[u8; 16] {
+    self.keymap.remap()
+  }
 
-    delay.delay_us(100);
+  fn write_pixels(&mut self, first_led: u8, colors: &[Color]) -> Result<(), Error<I2C::Error>> {
+    for (command, length) in pixel_writes(first_led, colors, self.pixels.color_order()) {
+      self.write_register(Module::Neopixel, NEOPIXEL_BUF, &command[..length])?;
+
+      self.seesaw.delay().delay_us(100);
+    }
 
     Ok(())
   }
 
-  pub fn show_led<DELAY: DelayUs<u32>>(&mut self, delay: &mut DELAY) -> Result<(), Error<I2C>> {
+  fn show_pixels(&mut self) -> Result<(), Error<I2C::Error>> {
+    let colors = self.pixels.output_colors();
+    while let Some((start, end)) = self.pixels.next_dirty_run() {
+      self.write_pixels(start, &colors[usize::from(start)..usize::from(end)])?;
+      self.pixels.mark_clean(start, end);
+    }
+
     self.write_register(Module::Neopixel, NEOPIXEL_SHOW, &[])?;
 
-    delay.delay_us(100);
+    self.seesaw.delay().delay_us(100);
+
+    Ok(())
+  }
+}
+
+impl<I2C, D> NeoTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Resets and configures the board at `address`.
+  ///
+  /// The driver keeps `delay` for the waits the seesaw protocol requires, so none
+  /// of the other methods need a delay argument.
+  pub fn new(bus: I2C, address: Address, delay: D) -> Result<Self, Error<I2C::Error>> {
+    NeoTrellis::uninitialized(bus, address, delay).init()
+  }
+
+  /// Sets up the board as `config` says, see `Config`.
+  pub fn with_config(bus: I2C, delay: D, config: Config) -> Result<Self, Error<I2C::Error>> {
+    let mut neotrellis = NeoTrellis::uninitialized(bus, config.address, delay);
+    neotrellis.neopixel_pin = config.neopixel_pin;
+    neotrellis.pixels.set_color_order(config.color_order);
+    neotrellis.pixels.set_brightness(config.brightness);
+    neotrellis.key_events = config.key_events;
+    neotrellis.seesaw.set_reset_timeout(config.reset_timeout_ms);
+
+    if config.reset {
+      neotrellis.soft_reset()?;
+    }
+    neotrellis.setup_neopixel()?;
+    neotrellis.setup_keypad()?;
+
+    Ok(neotrellis.into_state())
+  }
+
+  /// Creates the driver without any bus traffic, for a board that was set up
+  /// before, e.g. on a warm restart, skipping the reset and the wait for the board
+  /// to boot.
+  ///
+  /// The board is assumed to be configured as `new` leaves it, the cached LEDs
+  /// start black.
+  pub fn new_unchecked(bus: I2C, address: Address, delay: D) -> Self {
+    NeoTrellis::uninitialized(bus, address, delay).into_state()
+  }
+
+  /// Checks whether a seesaw answers at `address` with its hardware ID, without
+  /// resetting it or taking ownership of the bus, e.g. to find out which optional
+  /// panels are fitted before creating their drivers.
+  ///
+  /// A board that doesn't acknowledge its address gives `Ok(false)`.
+  pub fn probe(bus: &mut I2C, address: Address, delay: &mut D) -> Result<bool, Error<I2C::Error>> {
+    Seesaw::probe(bus, address, delay)
+  }
+
+  /// Probes every address the address jumpers can select, `NEOTRELLIS_ADDRESSES`,
+  /// returning the ones a seesaw answers at.
+  pub fn scan(bus: &mut I2C, delay: &mut D) -> Result<Vec<Address, 16>, Error<I2C::Error>> {
+    Self::scan_addresses(bus, delay, NEOTRELLIS_ADDRESSES)
+  }
+
+  /// Probes the addresses in `addresses`, returning the first 16 a seesaw answers
+  /// at.
+  pub fn scan_addresses(
+    bus: &mut I2C,
+    delay: &mut D,
+    addresses: RangeInclusive<u8>,
+  ) -> Result<Vec<Address, 16>, Error<I2C::Error>> {
+    let mut found = Vec::new();
+    for address in addresses {
+      let address = Address::new(address);
+      if Self::probe(bus, address, delay)? && found.push(address).is_err() {
+        break;
+      }
+    }
+
+    Ok(found)
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasNeopixel,
+{
+  /// Scales every color sent to the board by `brightness / 255`.
+  ///
+  /// The new brightness is applied to all LEDs by the next `show_led`.
+  pub fn set_brightness(&mut self, brightness: u8) {
+    self.pixels.set_brightness(brightness);
+  }
+
+  pub fn brightness(&self) -> u8 {
+    self.pixels.brightness()
+  }
+
+  /// Selects the seesaw pin driving the pixels, the NeoTrellis board uses pin 3.
+  ///
+  /// Only needed for other seesaw boards or custom firmware.
+  pub fn set_neopixel_pin(&mut self, pin: u8) -> Result<(), Error<I2C::Error>> {
+    self.neopixel_pin = pin;
+    self.write_register(Module::Neopixel, NEOPIXEL_PIN, &pin.to_be_bytes())
+  }
+
+  pub fn neopixel_pin(&self) -> u8 {
+    self.neopixel_pin
+  }
+
+  /// Sets the data rate of the pixels, the NeoTrellis LEDs run at 800 kHz.
+  pub fn set_neopixel_speed(&mut self, speed: Speed) -> Result<(), Error<I2C::Error>> {
+    self.speed = speed;
+    self.write_register(Module::Neopixel, NEOPIXEL_SPEED, &[speed.into()])
+  }
+
+  /// Selects the pixel layout of the attached LEDs, e.g. `ColorOrder::Grbw` for
+  /// RGBW strips, and resizes the seesaw pixel buffer accordingly.
+  pub fn set_color_order(&mut self, order: ColorOrder) -> Result<(), Error<I2C::Error>> {
+    self.pixels.set_color_order(order);
+
+    let buffer_length = self.pixels.buffer_length();
+    self.write_register(
+      Module::Neopixel,
+      NEOPIXEL_BUF_LENGTH,
+      &buffer_length.to_be_bytes(),
+    )
+  }
+
+  pub fn color_order(&self) -> ColorOrder {
+    self.pixels.color_order()
+  }
+
+  /// The settings `save_settings` stores.
+  pub fn settings(&self) -> Settings {
+    Settings {
+      brightness: self.brightness(),
+      rotation: self.rotation(),
+      color_order: self.color_order(),
+    }
+  }
+
+  pub fn apply_settings(&mut self, settings: Settings) -> Result<(), Error<I2C::Error>> {
+    self.set_brightness(settings.brightness);
+    self.set_rotation(settings.rotation);
+    self.set_color_order(settings.color_order)
+  }
+
+  /// Stores the brightness, rotation and color order in the seesaw EEPROM, so
+  /// `load_settings` can restore them after a power cycle.
+  pub fn save_settings(&mut self) -> Result<(), Error<I2C::Error>> {
+    let blob = self.settings().encode();
+    self.seesaw.eeprom_write(SETTINGS_OFFSET, &blob)
+  }
+
+  /// Applies the settings stored by `save_settings`, returning them. `None` if the
+  /// EEPROM holds none, e.g. on a new board, the current settings are kept then.
+  pub fn load_settings(&mut self) -> Result<Option<Settings>, Error<I2C::Error>> {
+    let mut blob = [0u8; SETTINGS_LENGTH];
+    self.seesaw.eeprom_read(SETTINGS_OFFSET, &mut blob)?;
+
+    let settings = Settings::decode(&blob);
+    if let Some(settings) = settings {
+      self.apply_settings(settings)?;
+    }
+
+    Ok(settings)
+  }
+
+  /// Enables the gamma-8 correction of every color sent to the board, so low
+  /// intensities look less washed out. Disabled by default.
+  pub fn set_gamma_correction(&mut self, enable: bool) {
+    self.pixels.set_gamma_correction(enable);
+  }
+
+  pub fn gamma_correction(&self) -> bool {
+    self.pixels.gamma_correction()
+  }
+
+  /// Scales the color of a single LED by `brightness / 255`, on top of the global
+  /// brightness. Applied to the LED by the next `show_led`.
+  ///
+  /// Fails with `Error::OutOfBounds` if `led` is not one of the 16 LEDs.
+  pub fn set_pixel_brightness(&mut self, led: u8, brightness: u8) -> Result<(), Error<I2C::Error>> {
+    if led >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    self
+      .pixels
+      .set_pixel_brightness(self.keymap.physical(led), brightness);
+
+    Ok(())
+  }
+
+  pub fn pixel_brightness(&self, led: u8) -> Option<u8> {
+    self.pixels.pixel_brightness(self.keymap.physical(led))
+  }
+
+  /// Sets the color of one LED, it is sent to the board by the next `show_led`.
+  ///
+  /// Fails with `Error::OutOfBounds` if `led` is not one of the 16 LEDs.
+  pub fn set_led_color(&mut self, led: u8, color: Color) -> Result<(), Error<I2C::Error>> {
+    if led >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    self.pixels.set(self.keymap.physical(led), color);
+
+    Ok(())
+  }
+
+  /// Sets the color of all 16 LEDs, they are sent to the board by the next `show_led`.
+  pub fn set_led_colors<C>(&mut self, colors: &[C; 16]) -> Result<(), Error<I2C::Error>>
+  where
+    C: Copy + Into<Color>,
+  {
+    for (led, color) in colors.iter().enumerate() {
+      self
+        .pixels
+        .set(self.keymap.physical(led as u8), (*color).into());
+    }
+
+    Ok(())
+  }
+
+  /// Sets all 16 LEDs to `color`, sent in one bulk write by the next `show_led`.
+  pub fn fill(&mut self, color: Color) {
+    for led in 0..16 {
+      self.pixels.set(led, color);
+    }
+  }
+
+  /// Turns all LEDs off with the next `show_led`.
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Copies the framebuffer to the board and shows it.
+  pub fn flush(&mut self, framebuffer: &Framebuffer) -> Result<(), Error<I2C::Error>> {
+    self.set_led_colors(framebuffer.board(0, 0))?;
+    self.show_led()
+  }
+
+  /// Sends the LEDs changed since the last call to the board and shows them.
+  pub fn show_led(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.recover_if_needed()?;
+    self.show_pixels()
+  }
+}
+
+impl<I2C, D, S> NeoTrellis<I2C, D, S>
+where
+  I2C: I2c,
+  D: DelayNs,
+  S: HasKeypad,
+{
+  pub fn set_key_event(
+    &mut self,
+    key: Key,
+    event: Event,
+    enable: bool,
+  ) -> Result<(), Error<I2C::Error>> {
+    let key = self.keymap.physical_key(key);
+    self.write_key_event(key, event, enable)
+  }
+
+  /// Makes every key report exactly `events`, e.g. only `Event::Falling` for a
+  /// simple button panel. Unlike `set_key_event` this is kept when the keypad is set
+  /// up again, e.g. by `recover`.
+  pub fn set_key_events(&mut self, events: EventSet) -> Result<(), Error<I2C::Error>> {
+    self.key_events = events;
+    self.setup_keypad()
+  }
+
+  /// Enables (or disables) the events in each `(key, events, enable)` entry, packing
+  /// up to 15 keys into each bus write instead of one write per key and event.
+  pub fn configure_keys(
+    &mut self,
+    keys: &[(Key, EventSet, bool)],
+  ) -> Result<(), Error<I2C::Error>> {
+    if keys.iter().any(|(key, _, _)| key.index() >= 16) {
+      return Err(Error::OutOfBounds);
+    }
+
+    for chunk in keys.chunks(KEY_COMMANDS_PER_WRITE) {
+      let commands = configure_commands(&self.keymap, chunk);
+      self.write_key_commands(&commands[..chunk.len()])?;
+    }
 
     Ok(())
   }
 
-  pub fn read_key_events<DELAY: DelayMs<u32>>(
+  /// Stops `key` from reporting any event, e.g. a key that is physically absent or
+  /// covered. Kept when the keypad is set up again.
+  pub fn disable_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    if key.index() >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    let key = self.keymap.physical_key(key);
+    self.disabled_keys |= 1 << key.index();
+
+    self.setup_key(key)
+  }
+
+  /// Makes a key disabled by `disable_key` report the configured events again.
+  pub fn enable_key(&mut self, key: Key) -> Result<(), Error<I2C::Error>> {
+    if key.index() >= 16 {
+      return Err(Error::OutOfBounds);
+    }
+    let key = self.keymap.physical_key(key);
+    self.disabled_keys &= !(1 << key.index());
+
+    self.setup_key(key)
+  }
+
+  pub fn key_enabled(&self, key: Key) -> bool {
+    key.index() < 16 && self.disabled_keys & (1 << self.keymap.physical_key(key).index()) == 0
+  }
+
+  /// Makes the board pull its INT line low while keypad events are waiting in the
+  /// FIFO.
+  ///
+  /// Instead of polling, the application can then watch the INT pin and only call
+  /// `read_key_events` once it goes low, the line is released when the FIFO has
+  /// been drained.
+  pub fn enable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.interrupt = true;
+    self.write_register(Module::Keypad, KEYPAD_INTENSET, &[0x01])
+  }
+
+  pub fn disable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.interrupt = false;
+    self.write_register(Module::Keypad, KEYPAD_INTENCLR, &[0x01])
+  }
+
+  /// Reads up to `events.len()` events from the keypad FIFO, only transferring as
+  /// many bytes as `keypad_count` reports.
+  ///
+  /// The events are stored at the start of `events`, the remaining slots are set to
+  /// `None`. Returns the number of events read, at most 32 per call.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, the events decoded from
+  /// the other bytes of the read are stored in `events` all the same, as the FIFO
+  /// has handed them over already.
+  pub fn read_key_events(
     &mut self,
     events: &mut [Option<KeypadEvent>],
-    delay: &mut DELAY,
-  ) -> Result<(), Error<I2C>> {
-    assert!(events.len() <= 32);
+  ) -> Result<usize, Error<I2C::Error>> {
     let mut buffer = [0u8; 32];
-    self.read_register(
-      Module::Keypad,
-      KEYPAD_FIFO,
-      &mut buffer[0..events.len()],
-      delay,
-    )?;
+    let length = events.len().min(buffer.len());
+    let length = self.read_fifo(&mut buffer[..length])?;
+
+    let mut count = 0;
+    let result = decode_fifo(
+      self.seesaw.address().value(),
+      &self.keymap,
+      &buffer[..length],
+      |event| {
+        events[count] = Some(event);
+        count += 1;
+      },
+    );
+    for event in events[count..].iter_mut() {
+      *event = None;
+    }
+
+    result.map(|()| count)
+  }
+
+  /// Iterates over the events currently waiting in the keypad FIFO, without any
+  /// scratch array:
+  ///
+  /// ```ignore
+  /// for event in trellis.events() {
+  ///   let event = event?;
+  ///   // ...
+  /// }
+  /// ```
+  pub fn events(&mut self) -> EventReader<'_, I2C, D, S> {
+    EventReader::new(self)
+  }
 
-    for (i, item) in buffer[0..events.len()].iter().enumerate() {
-      events[i] = if *item == 0xff {
-        None
-      } else {
-        Some(KeypadEvent {
-          key: Key::deserialize(item >> 2),
-          event: Event::try_from(item & 0x03).unwrap(),
-        })
-      };
+  /// Blocks until the board pulls `int_pin` low or `timeout_ms` milliseconds have
+  /// passed, then reads the pending events into `events`.
+  ///
+  /// The keypad interrupt must be enabled, see `enable_interrupt`. Returns the number
+  /// of events read, which is 0 on timeout.
+  pub fn wait_for_event<P: InputPin>(
+    &mut self,
+    int_pin: &mut P,
+    timeout_ms: u32,
+    events: &mut [Option<KeypadEvent>],
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut waited_ms = 0;
+    while !int_pin.is_low().map_err(|e| Error::PinError(e.kind()))? {
+      if waited_ms >= timeout_ms {
+        return Ok(0);
+      }
+
+      self.seesaw.delay().delay_ms(1);
+      waited_ms += 1;
     }
 
-    Ok(())
+    self.read_key_events(events)
+  }
+
+  /// Returns the next keypad event, or `WouldBlock` when the FIFO is empty.
+  ///
+  /// Meant for superloops: call it on every iteration and do other work in between
+  /// instead of waiting for events.
+  pub fn poll_event(&mut self) -> nb::Result<KeypadEvent, Error<I2C::Error>> {
+    if self.keypad_count()? == 0 {
+      return Err(nb::Error::WouldBlock);
+    }
+
+    let mut value = [0u8];
+    self.read_register(Module::Keypad, KEYPAD_FIFO, &mut value)?;
+
+    decode_event(self.seesaw.address().value(), &self.keymap, value[0])?
+      .ok_or(nb::Error::WouldBlock)
+  }
+
+  /// Reads the FIFO until it is empty, calling `handler` with every event. Returns
+  /// the number of events handled.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, after the other events
+  /// of the same read were handled.
+  pub fn drain_events<F: FnMut(KeypadEvent)>(
+    &mut self,
+    mut handler: F,
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut buffer = [0u8; 32];
+    let mut handled = 0;
+
+    loop {
+      let length = self.read_fifo(&mut buffer)?;
+      if length == 0 {
+        return Ok(handled);
+      }
+
+      let before = handled;
+      decode_fifo(
+        self.seesaw.address().value(),
+        &self.keymap,
+        &buffer[..length],
+        |event| {
+          handler(event);
+          handled += 1;
+        },
+      )?;
+
+      // Don't spin forever on a count that doesn't match the FIFO contents
+      if handled == before {
+        return Ok(handled);
+      }
+    }
+  }
+
+  /// Moves pending events from the keypad FIFO into `queue`, returning how many were
+  /// queued.
+  ///
+  /// Only as many events as `queue` has room for are read, the others stay in the
+  /// FIFO for the next call.
+  ///
+  /// A corrupted FIFO byte fails with `Error::ProtocolError`, after the other events
+  /// of the same read were queued.
+  pub fn pump<const N: usize>(
+    &mut self,
+    queue: &mut EventProducer<'_, N>,
+  ) -> Result<usize, Error<I2C::Error>> {
+    let mut buffer = [0u8; 32];
+    let mut queued = 0;
+
+    loop {
+      let room = (queue.capacity() - queue.len()).min(buffer.len());
+      if room == 0 {
+        return Ok(queued);
+      }
+
+      let length = self.read_fifo(&mut buffer[..room])?;
+      let before = queued;
+      decode_fifo(
+        self.seesaw.address().value(),
+        &self.keymap,
+        &buffer[..length],
+        |event| {
+          // Can't fail, at most `room` events were read
+          let _ = queue.enqueue(event);
+          queued += 1;
+        },
+      )?;
+
+      if queued == before {
+        return Ok(queued);
+      }
+    }
+  }
+
+  /// Reads as many pending events as `keypad_count` reports and `buffer` can hold,
+  /// returning the number of bytes read.
+  pub(crate) fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<usize, Error<I2C::Error>> {
+    let count = usize::from(self.keypad_count()?).min(buffer.len());
+    if count > 0 {
+      self.read_register(Module::Keypad, KEYPAD_FIFO, &mut buffer[..count])?;
+    }
+
+    Ok(count)
   }
 
-  pub fn keypad_count<DELAY: DelayMs<u32>>(&mut self, delay: &mut DELAY) -> Result<u8, Error<I2C>> {
+  pub fn keypad_count(&mut self) -> Result<u8, Error<I2C::Error>> {
+    self.recover_if_needed()?;
     let mut value = [0u8];
-    self.read_register(Module::Keypad, KEYPAD_COUNT, &mut value, delay)?;
+    self.read_register(Module::Keypad, KEYPAD_COUNT, &mut value)?;
 
     let count = u8::from_be_bytes(value);
 
@@ -0,0 +1,170 @@
+//! Mapping between key positions and MIDI note numbers, for Launchpad style
+//! controllers.
+//!
+//! Notes go up from left to right and from the bottom row to the top row.
+//!
+//! ```ignore
+//! let notes = NoteMap::new(NoteLayout::InKey { root: 48, scale: MAJOR, row_interval: 3 }, 8, 8);
+//! trellis.drain_events(|event| {
+//!   if let Some(note) = notes.note(event.coordinate) {
+//!     send_note(note, event.is_press());
+//!   }
+//! })?;
+//! ```
+
+/// Semitones of the major scale.
+pub const MAJOR: &[u8] = &[0, 2, 4, 5, 7, 9, 11];
+/// Semitones of the natural minor scale.
+pub const MINOR: &[u8] = &[0, 2, 3, 5, 7, 8, 10];
+pub const MAJOR_PENTATONIC: &[u8] = &[0, 2, 4, 7, 9];
+pub const MINOR_PENTATONIC: &[u8] = &[0, 3, 5, 7, 10];
+
+/// How notes are laid out on the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteLayout {
+  /// Every key is one semitone above its left neighbour, every row is
+  /// `row_interval` semitones above the row below (5 gives the fourths of a bass
+  /// guitar).
+  Chromatic { root: u8, row_interval: u8 },
+  /// Only the notes of `scale`, given as semitones from `root` within one octave.
+  /// Every row starts `row_interval` scale degrees above the row below.
+  InKey {
+    root: u8,
+    scale: &'static [u8],
+    row_interval: u8,
+  },
+  /// Drum pads: every 4x4 block holds 16 consecutive notes from its bottom left
+  /// key, the blocks follow each other left to right and then bottom to top.
+  /// With `base` 36 a single board covers the General MIDI kick to the toms.
+  Drum { base: u8 },
+}
+
+/// Maps the keys of a grid `width` keys wide and `height` keys high to notes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoteMap {
+  layout: NoteLayout,
+  width: u8,
+  height: u8,
+}
+
+impl NoteMap {
+  pub const fn new(layout: NoteLayout, width: u8, height: u8) -> Self {
+    Self {
+      layout,
+      width,
+      height,
+    }
+  }
+
+  pub fn layout(&self) -> NoteLayout {
+    self.layout
+  }
+
+  pub fn set_layout(&mut self, layout: NoteLayout) {
+    self.layout = layout;
+  }
+
+  /// Note of the key at `(x, y)`, `None` outside of the grid or above note 127.
+  pub fn note(&self, (x, y): (u8, u8)) -> Option<u8> {
+    if x >= self.width || y >= self.height {
+      return None;
+    }
+    let (x, row) = (u32::from(x), u32::from(self.height - 1 - y));
+
+    let note = match self.layout {
+      NoteLayout::Chromatic { root, row_interval } => {
+        u32::from(root) + x + row * u32::from(row_interval)
+      }
+      NoteLayout::InKey {
+        root,
+        scale,
+        row_interval,
+      } => {
+        if scale.is_empty() {
+          return None;
+        }
+        let degree = x + row * u32::from(row_interval);
+        let length = scale.len() as u32;
+        u32::from(root) + 12 * (degree / length) + u32::from(scale[(degree % length) as usize])
+      }
+      NoteLayout::Drum { base } => {
+        let blocks_per_row = u32::from(self.width).div_ceil(4);
+        let block = x / 4 + (row / 4) * blocks_per_row;
+        u32::from(base) + 16 * block + x % 4 + 4 * (row % 4)
+      }
+    };
+
+    if note <= 127 {
+      Some(note as u8)
+    } else {
+      None
+    }
+  }
+
+  /// Position of the lowest, then leftmost key playing `note`.
+  pub fn position(&self, note: u8) -> Option<(u8, u8)> {
+    (0..self.height)
+      .rev()
+      .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+      .find(|position| self.note(*position) == Some(note))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{NoteLayout, NoteMap, MAJOR};
+
+  #[test]
+  fn chromatic_rows_go_up_by_the_interval() {
+    let notes = NoteMap::new(
+      NoteLayout::Chromatic {
+        root: 36,
+        row_interval: 5,
+      },
+      8,
+      8,
+    );
+    assert_eq!(notes.note((0, 7)), Some(36));
+    assert_eq!(notes.note((1, 7)), Some(37));
+    assert_eq!(notes.note((0, 6)), Some(41));
+    assert_eq!(notes.note((8, 0)), None);
+    assert_eq!(notes.position(41), Some((5, 7)));
+  }
+
+  #[test]
+  fn in_key_skips_the_notes_outside_of_the_scale() {
+    let layout = NoteLayout::InKey {
+      root: 48,
+      scale: MAJOR,
+      row_interval: 3,
+    };
+    let notes = NoteMap::new(layout, 8, 8);
+    assert_eq!(notes.note((1, 7)), Some(50));
+    assert_eq!(notes.note((7, 7)), Some(60));
+    assert_eq!(notes.note((0, 6)), Some(53));
+    assert_eq!(notes.position(49), None);
+  }
+
+  #[test]
+  fn drum_blocks_hold_16_notes() {
+    let notes = NoteMap::new(NoteLayout::Drum { base: 36 }, 8, 8);
+    assert_eq!(notes.note((3, 7)), Some(39));
+    assert_eq!(notes.note((0, 4)), Some(48));
+    assert_eq!(notes.note((4, 7)), Some(52));
+    assert_eq!(notes.note((0, 3)), Some(68));
+  }
+
+  #[test]
+  fn notes_above_127_are_none() {
+    let notes = NoteMap::new(
+      NoteLayout::Chromatic {
+        root: 120,
+        row_interval: 5,
+      },
+      8,
+      2,
+    );
+    assert_eq!(notes.note((7, 1)), Some(127));
+    assert_eq!(notes.note((3, 0)), None);
+  }
+}
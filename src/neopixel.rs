@@ -0,0 +1,186 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::pixels::{encode_pixels, pixels_per_write, MAX_PIXEL_COMMAND};
+use crate::Seesaw;
+use crate::{
+  Address, Color, ColorOrder, Error, Module, Speed, NEOPIXEL_BUF, NEOPIXEL_BUF_LENGTH,
+  NEOPIXEL_PIN, NEOPIXEL_SHOW, NEOPIXEL_SPEED,
+};
+
+/// Driver for a strip of pixels on any pin of a seesaw chip, e.g. an external strip
+/// on a seesaw breakout.
+///
+/// Unlike the pixels of a `NeoTrellis` the colors aren't cached, `write` sends
+/// them to the seesaw buffer right away and `show` displays the buffer. How many
+/// pixels the buffer holds depends on the firmware.
+pub struct SeesawNeoPixel<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  seesaw: Seesaw<I2C, D>,
+  pin: u8,
+  count: u16,
+  color_order: ColorOrder,
+  brightness: u8,
+}
+
+impl<I2C, D> SeesawNeoPixel<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Resets the seesaw at `address` and sets up `count` pixels on `pin`.
+  pub fn new(
+    bus: I2C,
+    address: Address,
+    delay: D,
+    pin: u8,
+    count: u16,
+    color_order: ColorOrder,
+  ) -> Result<Self, Error<I2C::Error>> {
+    let mut seesaw = Seesaw::new(bus, address, delay);
+    seesaw.soft_reset()?;
+
+    Self::from_seesaw(seesaw, pin, count, color_order)
+  }
+
+  /// Sets up `count` pixels on `pin` of a seesaw that is already running, e.g.
+  /// one whose other modules are used as well.
+  pub fn from_seesaw(
+    seesaw: Seesaw<I2C, D>,
+    pin: u8,
+    count: u16,
+    color_order: ColorOrder,
+  ) -> Result<Self, Error<I2C::Error>> {
+    let mut neopixel = Self {
+      seesaw,
+      pin,
+      count,
+      color_order,
+      brightness: 255,
+    };
+    neopixel.set_pin(pin)?;
+    neopixel.write_buffer_length()?;
+
+    Ok(neopixel)
+  }
+
+  pub fn set_pin(&mut self, pin: u8) -> Result<(), Error<I2C::Error>> {
+    self.pin = pin;
+    self
+      .seesaw
+      .write(Module::Neopixel.into(), NEOPIXEL_PIN, &[pin])
+  }
+
+  pub fn pin(&self) -> u8 {
+    self.pin
+  }
+
+  pub fn set_count(&mut self, count: u16) -> Result<(), Error<I2C::Error>> {
+    self.count = count;
+    self.write_buffer_length()
+  }
+
+  pub fn count(&self) -> u16 {
+    self.count
+  }
+
+  pub fn set_color_order(&mut self, order: ColorOrder) -> Result<(), Error<I2C::Error>> {
+    self.color_order = order;
+    self.write_buffer_length()
+  }
+
+  pub fn color_order(&self) -> ColorOrder {
+    self.color_order
+  }
+
+  pub fn set_speed(&mut self, speed: Speed) -> Result<(), Error<I2C::Error>> {
+    self
+      .seesaw
+      .write(Module::Neopixel.into(), NEOPIXEL_SPEED, &[speed.into()])
+  }
+
+  /// Scales the colors sent by the following writes, 255 sends them unchanged.
+  pub fn set_brightness(&mut self, brightness: u8) {
+    self.brightness = brightness;
+  }
+
+  pub fn brightness(&self) -> u8 {
+    self.brightness
+  }
+
+  fn write_buffer_length(&mut self) -> Result<(), Error<I2C::Error>> {
+    let length = self.count * self.color_order.bytes_per_pixel() as u16;
+    self.seesaw.write(
+      Module::Neopixel.into(),
+      NEOPIXEL_BUF_LENGTH,
+      &length.to_be_bytes(),
+    )
+  }
+
+  /// Writes `colors` to the pixels starting at `first`, they are displayed by the
+  /// next `show`.
+  pub fn write<C>(&mut self, first: u16, colors: &[C]) -> Result<(), Error<I2C::Error>>
+  where
+    C: Copy + Into<Color>,
+  {
+    if usize::from(first) + colors.len() > usize::from(self.count) {
+      return Err(Error::OutOfBounds);
+    }
+
+    let order = self.color_order;
+    let per_write = pixels_per_write(order);
+    let mut scaled = [Color::BLACK; MAX_PIXEL_COMMAND];
+    let mut command = [0u8; MAX_PIXEL_COMMAND];
+    for (i, chunk) in colors.chunks(per_write).enumerate() {
+      for (scaled, color) in scaled.iter_mut().zip(chunk) {
+        *scaled = (*color).into().scale(self.brightness);
+      }
+      let pixel = first + (i * per_write) as u16;
+      let length = encode_pixels(pixel, &scaled[..chunk.len()], order, &mut command);
+      self
+        .seesaw
+        .write(Module::Neopixel.into(), NEOPIXEL_BUF, &command[..length])?;
+
+      self.seesaw.delay().delay_us(100);
+    }
+
+    Ok(())
+  }
+
+  /// Writes `color` to every pixel.
+  pub fn fill(&mut self, color: Color) -> Result<(), Error<I2C::Error>> {
+    let per_write = pixels_per_write(self.color_order) as u16;
+    let colors = [color; MAX_PIXEL_COMMAND];
+    let mut first = 0;
+    while first < self.count {
+      let length = per_write.min(self.count - first);
+      self.write(first, &colors[..usize::from(length)])?;
+      first += length;
+    }
+
+    Ok(())
+  }
+
+  /// Displays the colors written so far.
+  pub fn show(&mut self) -> Result<(), Error<I2C::Error>> {
+    self
+      .seesaw
+      .write(Module::Neopixel.into(), NEOPIXEL_SHOW, &[])?;
+    self.seesaw.delay().delay_us(100);
+
+    Ok(())
+  }
+
+  /// The seesaw transport, e.g. for the other modules of the chip.
+  pub fn seesaw(&mut self) -> &mut Seesaw<I2C, D> {
+    &mut self.seesaw
+  }
+
+  /// Gives back the bus and the delay.
+  pub fn free(self) -> (I2C, D) {
+    self.seesaw.free()
+  }
+}
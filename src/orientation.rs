@@ -0,0 +1,145 @@
+use crate::{Key, KeypadEvent};
+
+/// Clockwise rotation of a board relative to the way it is looked at.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+  #[default]
+  Deg0,
+  Deg90,
+  Deg180,
+  Deg270,
+}
+
+impl Rotation {
+  /// Maps a logical `(x, y)` position, as seen by the user, to the physical position
+  /// on a board rotated by `self`.
+  pub(crate) const fn to_physical(self, x: u8, y: u8) -> (u8, u8) {
+    match self {
+      Rotation::Deg0 => (x, y),
+      Rotation::Deg90 => (y, 3 - x),
+      Rotation::Deg180 => (3 - x, 3 - y),
+      Rotation::Deg270 => (3 - y, x),
+    }
+  }
+
+  /// Inverse of `to_physical`.
+  pub(crate) const fn to_logical(self, x: u8, y: u8) -> (u8, u8) {
+    match self {
+      Rotation::Deg0 => (x, y),
+      Rotation::Deg90 => (3 - y, x),
+      Rotation::Deg180 => (3 - x, 3 - y),
+      Rotation::Deg270 => (y, 3 - x),
+    }
+  }
+}
+
+/// Translation between the logical key and LED indices used by the application and
+/// the physical ones used by the seesaw.
+pub(crate) struct KeyMap {
+  rotation: Rotation,
+  mirror_x: bool,
+  mirror_y: bool,
+  remap: [u8; 16],
+  to_physical: [u8; 16],
+  to_logical: [u8; 16],
+}
+
+impl KeyMap {
+  pub(crate) const fn new() -> Self {
+    Self {
+      rotation: Rotation::Deg0,
+      mirror_x: false,
+      mirror_y: false,
+      remap: IDENTITY,
+      to_physical: IDENTITY,
+      to_logical: IDENTITY,
+    }
+  }
+
+  pub(crate) fn rotation(&self) -> Rotation {
+    self.rotation
+  }
+
+  pub(crate) fn set_rotation(&mut self, rotation: Rotation) {
+    self.rotation = rotation;
+    self.update();
+  }
+
+  pub(crate) fn mirroring(&self) -> (bool, bool) {
+    (self.mirror_x, self.mirror_y)
+  }
+
+  pub(crate) fn set_mirroring(&mut self, mirror_x: bool, mirror_y: bool) {
+    self.mirror_x = mirror_x;
+    self.mirror_y = mirror_y;
+    self.update();
+  }
+
+  pub(crate) fn remap(&self) -> &[u8; 16] {
+    &self.remap
+  }
+
+  pub(crate) fn set_remap(&mut self, remap: [u8; 16]) {
+    let mut seen = 0u16;
+    for target in remap.iter() {
+      assert!(*target < 16, "remap entries must be key indices");
+      seen |= 1 << target;
+    }
+    assert!(seen == 0xffff, "remap must map every key exactly once");
+
+    self.remap = remap;
+    self.update();
+  }
+
+  /// Physical index of the logical `index`, indices past the board are kept as is.
+  pub(crate) fn physical(&self, index: u8) -> u8 {
+    self
+      .to_physical
+      .get(usize::from(index))
+      .copied()
+      .unwrap_or(index)
+  }
+
+  pub(crate) fn logical(&self, index: u8) -> u8 {
+    self
+      .to_logical
+      .get(usize::from(index))
+      .copied()
+      .unwrap_or(index)
+  }
+
+  pub(crate) fn physical_key(&self, key: Key) -> Key {
+    Key::from_index(self.physical(key.index()))
+  }
+
+  /// `event` of a physical key, translated to the logical key.
+  pub(crate) fn logical_event(&self, event: KeypadEvent) -> KeypadEvent {
+    KeypadEvent {
+      key: Key::from_index(self.logical(event.key.index())),
+      event: event.event,
+    }
+  }
+
+  fn update(&mut self) {
+    for logical in 0..16u8 {
+      // The remap table relabels keys, then mirroring happens in the view, before
+      // undoing the rotation of the board
+      let remapped = self.remap[usize::from(logical)];
+      let (mut x, mut y) = (remapped % 4, remapped / 4);
+      if self.mirror_x {
+        x = 3 - x;
+      }
+      if self.mirror_y {
+        y = 3 - y;
+      }
+
+      let (x, y) = self.rotation.to_physical(x, y);
+      let physical = x + 4 * y;
+
+      self.to_physical[usize::from(logical)] = physical;
+      self.to_logical[usize::from(physical)] = logical;
+    }
+  }
+}
+
+const IDENTITY: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
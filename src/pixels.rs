@@ -0,0 +1,196 @@
+use crate::{Color, ColorOrder};
+
+/// Pixel data that fits in one `NEOPIXEL_BUF` write next to the 2 byte buffer offset.
+const MAX_PIXEL_DATA: usize = 29;
+
+pub(crate) const MAX_PIXEL_COMMAND: usize = 2 + MAX_PIXEL_DATA;
+
+pub(crate) const fn pixels_per_write(order: ColorOrder) -> usize {
+  MAX_PIXEL_DATA / order.bytes_per_pixel()
+}
+
+/// Encodes a `NEOPIXEL_BUF` write of up to `pixels_per_write` colors starting at
+/// pixel `first_led`, returning the length of the command.
+pub(crate) fn encode_pixels(
+  first_led: u16,
+  colors: &[Color],
+  order: ColorOrder,
+  command: &mut [u8; MAX_PIXEL_COMMAND],
+) -> usize {
+  let stride = order.bytes_per_pixel();
+  let count = colors.len().min(pixels_per_write(order));
+
+  let led_address = first_led * stride as u16;
+  command[0..2].copy_from_slice(&led_address.to_be_bytes());
+
+  for (i, color) in colors[..count].iter().enumerate() {
+    order.encode(*color, &mut command[(2 + stride * i)..]);
+  }
+
+  2 + stride * count
+}
+
+/// The `NEOPIXEL_BUF` writes sending `colors` from pixel `first_led` on, each as
+/// `(command, length)` with as many pixels as fit into one write.
+pub(crate) fn pixel_writes(
+  first_led: u8,
+  colors: &[Color],
+  order: ColorOrder,
+) -> impl Iterator<Item = ([u8; MAX_PIXEL_COMMAND], usize)> + '_ {
+  let per_write = pixels_per_write(order);
+  colors.chunks(per_write).enumerate().map(move |(i, chunk)| {
+    let mut command = [0u8; MAX_PIXEL_COMMAND];
+    let led = first_led + (i * per_write) as u8;
+    let length = encode_pixels(u16::from(led), chunk, order, &mut command);
+    (command, length)
+  })
+}
+
+/// The usual WS2812 gamma-8 table (gamma 2.8), mapping linear intensities to
+/// perceptually even steps.
+#[rustfmt::skip]
+pub(crate) static GAMMA8: [u8; 256] = [
+  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1,
+  1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2,
+  2, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5,
+  5, 6, 6, 6, 6, 7, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10,
+  10, 10, 11, 11, 11, 12, 12, 13, 13, 13, 14, 14, 15, 15, 16, 16,
+  17, 17, 18, 18, 19, 19, 20, 20, 21, 21, 22, 22, 23, 24, 24, 25,
+  25, 26, 27, 27, 28, 29, 29, 30, 31, 32, 32, 33, 34, 35, 35, 36,
+  37, 38, 39, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 50,
+  51, 52, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 66, 67, 68,
+  69, 70, 72, 73, 74, 75, 77, 78, 79, 81, 82, 83, 85, 86, 87, 89,
+  90, 92, 93, 95, 96, 98, 99, 101, 102, 104, 105, 107, 109, 110, 112, 114,
+  115, 117, 119, 120, 122, 124, 126, 127, 129, 131, 133, 135, 137, 138, 140, 142,
+  144, 146, 148, 150, 152, 154, 156, 158, 160, 162, 164, 167, 169, 171, 173, 175,
+  177, 180, 182, 184, 186, 189, 191, 193, 196, 198, 200, 203, 205, 208, 210, 213,
+  215, 218, 220, 223, 225, 228, 231, 233, 236, 239, 241, 244, 247, 249, 252, 255,
+];
+
+/// Local copy of the seesaw pixel buffer, tracking which LEDs have changed since
+/// they were last sent so `show_led` only transmits those.
+pub(crate) struct PixelBuffer {
+  colors: [Color; 16],
+  dirty: u16,
+  brightness: u8,
+  pixel_brightness: [u8; 16],
+  gamma_correction: bool,
+  color_order: ColorOrder,
+}
+
+impl PixelBuffer {
+  /// The seesaw clears its pixel buffer on reset, so the cache starts black and clean.
+  pub(crate) const fn new() -> Self {
+    Self {
+      colors: [Color::BLACK; 16],
+      dirty: 0,
+      brightness: 255,
+      pixel_brightness: [255; 16],
+      gamma_correction: false,
+      color_order: ColorOrder::Grb,
+    }
+  }
+
+  /// Colors as they have to be written to the board, with the global and the per
+  /// pixel brightness and then the gamma correction applied.
+  pub(crate) fn output_colors(&self) -> [Color; 16] {
+    let mut output = self.colors;
+    for (color, pixel_brightness) in output.iter_mut().zip(self.pixel_brightness.iter()) {
+      *color = color.scale(self.brightness).scale(*pixel_brightness);
+      if self.gamma_correction {
+        for channel in [&mut color.r, &mut color.g, &mut color.b, &mut color.w] {
+          *channel = GAMMA8[usize::from(*channel)];
+        }
+      }
+    }
+
+    output
+  }
+
+  pub(crate) fn brightness(&self) -> u8 {
+    self.brightness
+  }
+
+  pub(crate) fn set_brightness(&mut self, brightness: u8) {
+    if brightness != self.brightness {
+      self.brightness = brightness;
+      self.dirty = 0xffff;
+    }
+  }
+
+  pub(crate) fn set(&mut self, led: u8, color: Color) {
+    let led = usize::from(led);
+    if led < self.colors.len() && self.colors[led] != color {
+      self.colors[led] = color;
+      self.dirty |= 1 << led;
+    }
+  }
+
+  pub(crate) fn color_order(&self) -> ColorOrder {
+    self.color_order
+  }
+
+  /// Length in bytes of the seesaw pixel buffer for the current color order.
+  pub(crate) fn buffer_length(&self) -> u16 {
+    (self.colors.len() * self.color_order.bytes_per_pixel()) as u16
+  }
+
+  /// The pixel layout changed, so the whole buffer has to be sent again.
+  pub(crate) fn set_color_order(&mut self, order: ColorOrder) {
+    self.color_order = order;
+    self.dirty = 0xffff;
+  }
+
+  pub(crate) fn gamma_correction(&self) -> bool {
+    self.gamma_correction
+  }
+
+  pub(crate) fn set_gamma_correction(&mut self, enable: bool) {
+    if enable != self.gamma_correction {
+      self.gamma_correction = enable;
+      self.dirty = 0xffff;
+    }
+  }
+
+  pub(crate) fn pixel_brightness(&self, led: u8) -> Option<u8> {
+    self.pixel_brightness.get(usize::from(led)).copied()
+  }
+
+  pub(crate) fn set_pixel_brightness(&mut self, led: u8, brightness: u8) {
+    let led = usize::from(led);
+    if led < self.pixel_brightness.len() && self.pixel_brightness[led] != brightness {
+      self.pixel_brightness[led] = brightness;
+      self.dirty |= 1 << led;
+    }
+  }
+
+  /// The board lost its pixels, so all of them have to be sent again.
+  pub(crate) fn mark_dirty(&mut self) {
+    self.dirty = 0xffff;
+  }
+
+  /// The board was reset and shows black, the cache follows.
+  pub(crate) fn reset(&mut self) {
+    self.colors = [Color::BLACK; 16];
+    self.dirty = 0;
+  }
+
+  /// Returns the first contiguous range `start..end` of changed LEDs.
+  pub(crate) fn next_dirty_run(&self) -> Option<(u8, u8)> {
+    if self.dirty == 0 {
+      return None;
+    }
+
+    let start = self.dirty.trailing_zeros();
+    let end = start + (self.dirty >> start).trailing_ones();
+
+    Some((start as u8, end as u8))
+  }
+
+  pub(crate) fn mark_clean(&mut self, start: u8, end: u8) {
+    for led in start..end {
+      self.dirty &= !(1 << led);
+    }
+  }
+}
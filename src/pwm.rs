@@ -0,0 +1,53 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::Seesaw;
+use crate::{Error, HardwareId, Module};
+
+const TIMER_PWM: u8 = 0x01;
+const TIMER_FREQ: u8 = 0x02;
+
+/// PWM outputs of the seesaw timer module, e.g. to drive a buzzer for key clicks.
+///
+/// Which pins can output PWM depends on the chip: 4 to 7 on the SAMD09, 0, 1, 7, 11
+/// and 16 on the ATtiny8x6 and 0, 1, 9, 12 and 13 on the ATtiny8x7. Other pins fail
+/// with `Error::OutOfBounds`.
+impl<I2C, D> Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Sets the duty cycle of `pin`, from 0 (always low) to 0xffff (always high).
+  pub fn set_pwm_duty(&mut self, pin: u8, duty: u16) -> Result<(), Error<I2C::Error>> {
+    let index = self.pwm_index(pin)?;
+    let [high, low] = duty.to_be_bytes();
+    self.write(Module::Timer.into(), TIMER_PWM, &[index, high, low])
+  }
+
+  /// Sets the PWM frequency of `pin` in Hz, e.g. the pitch of a buzzer.
+  pub fn set_pwm_frequency(&mut self, pin: u8, frequency_hz: u16) -> Result<(), Error<I2C::Error>> {
+    let index = self.pwm_index(pin)?;
+    let [high, low] = frequency_hz.to_be_bytes();
+    self.write(Module::Timer.into(), TIMER_FREQ, &[index, high, low])
+  }
+
+  /// The timer output of `pin`: the SAMD09 firmware numbers its PWM outputs, the
+  /// ATtiny firmware takes the pin itself.
+  fn pwm_index(&mut self, pin: u8) -> Result<u8, Error<I2C::Error>> {
+    let id = self.hardware_id()?;
+    let pins: &[u8] = match id {
+      HardwareId::Samd09 => &[4, 5, 6, 7],
+      HardwareId::Attiny806 | HardwareId::Attiny816 | HardwareId::Attiny1616 => &[0, 1, 7, 11, 16],
+      HardwareId::Attiny807 | HardwareId::Attiny817 | HardwareId::Attiny1617 => &[0, 1, 9, 12, 13],
+    };
+    let index = pins
+      .iter()
+      .position(|p| *p == pin)
+      .ok_or(Error::OutOfBounds)?;
+
+    Ok(match id {
+      HardwareId::Samd09 => index as u8,
+      _ => pin,
+    })
+  }
+}
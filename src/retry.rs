@@ -0,0 +1,27 @@
+/// How register reads and writes are retried when the bus reports an error, e.g.
+/// the NACK of a seesaw that is busy processing the previous command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+  /// Number of tries, including the first one.
+  pub attempts: u8,
+  /// Wait before every retry, in microseconds.
+  pub backoff_us: u32,
+}
+
+impl RetryPolicy {
+  /// Every error is returned right away.
+  pub const NONE: Self = Self::new(1, 0);
+
+  pub const fn new(attempts: u8, backoff_us: u32) -> Self {
+    Self {
+      attempts,
+      backoff_us,
+    }
+  }
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self::NONE
+  }
+}
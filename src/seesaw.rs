@@ -0,0 +1,345 @@
+use core::convert::TryFrom;
+
+use crate::{
+  Address, Capabilities, Error, FirmwareVersion, HardwareId, Module, RetryPolicy, Stats,
+  Temperature, RESET_POLL_MS, STATUS_HW_ID, STATUS_OPTIONS, STATUS_SWRST, STATUS_TEMP,
+  STATUS_VERSION,
+};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{Error as _, ErrorKind, I2c};
+
+/// Wait between the register address and the data of a read, long enough for the
+/// keypad FIFO.
+const READ_DELAY_US: u32 = 6000;
+/// Longest register value of a write, the seesaw I2C buffer holds 32 bytes with the
+/// module and register.
+const MAX_WRITE_LENGTH: usize = 31;
+
+/// Register access to one seesaw chip, the transport `NeoTrellis` is built on.
+///
+/// Other seesaw based boards, or the modules of custom seesaw firmware, can be
+/// driven through `read` and `write`, with the same retries and reset handling.
+pub struct Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  bus: I2C,
+  delay: D,
+  pub(crate) address: u8,
+  retry: RetryPolicy,
+  /// Register accesses that failed in a row.
+  errors: u8,
+  reset_timeout_ms: u32,
+  stats: Stats,
+}
+
+impl<I2C, D> Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  /// Creates the transport without any bus traffic.
+  ///
+  /// The seesaw protocol needs waits between the register address and the data of
+  /// a read, `delay` is kept for them.
+  pub fn new(bus: I2C, address: Address, delay: D) -> Self {
+    Self {
+      bus,
+      delay,
+      address: address.value(),
+      retry: RetryPolicy::NONE,
+      errors: 0,
+      reset_timeout_ms: 500,
+      stats: Stats::new(),
+    }
+  }
+
+  /// Checks whether a seesaw answers at `address` with its hardware ID, without
+  /// resetting it or taking ownership of the bus.
+  ///
+  /// A chip that doesn't acknowledge its address gives `Ok(false)`.
+  pub fn probe(bus: &mut I2C, address: Address, delay: &mut D) -> Result<bool, Error<I2C::Error>> {
+    let address = address.value();
+    let command = [Module::Status.into(), STATUS_HW_ID];
+    match bus.write(address, &command) {
+      Err(error) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => return Ok(false),
+      result => result.map_err(|error| Error::WriteError {
+        address,
+        module: command[0],
+        register: STATUS_HW_ID,
+        error,
+      })?,
+    }
+
+    delay.delay_ms(6);
+
+    let mut id = [0u8];
+    bus
+      .read(address, &mut id)
+      .map_err(|error| Error::ReadError {
+        address,
+        module: command[0],
+        register: STATUS_HW_ID,
+        error,
+      })?;
+
+    Ok(HardwareId::try_from(id[0]).is_ok())
+  }
+
+  /// Resets the seesaw, then polls its hardware ID until the chip answers or the
+  /// reset timeout is over.
+  pub fn soft_reset(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.write(Module::Status.into(), STATUS_SWRST, &[0xff])?;
+
+    let mut id = [0u8];
+    let mut waited_ms = 0;
+    loop {
+      self.delay.delay_ms(RESET_POLL_MS);
+      waited_ms += RESET_POLL_MS;
+
+      // The seesaw doesn't answer while it boots
+      let result = self.read_once(Module::Status.into(), STATUS_HW_ID, &mut id, READ_DELAY_US);
+      if result.is_ok() && HardwareId::try_from(id[0]).is_ok() {
+        return Ok(());
+      }
+      if waited_ms >= self.reset_timeout_ms {
+        result?;
+        return Err(Error::WrongChipId {
+          address: self.address,
+          id: id[0],
+        });
+      }
+    }
+  }
+
+  /// Reads `value.len()` bytes from `register` of the seesaw `module`, retrying as
+  /// the retry policy says.
+  pub fn read(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &mut [u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    self.read_delayed(module, register, value, READ_DELAY_US)
+  }
+
+  /// Like `read`, waiting `delay_us` for the chip to prepare the value, for the
+  /// registers that are answered faster than the keypad.
+  pub(crate) fn read_delayed(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &mut [u8],
+    delay_us: u32,
+  ) -> Result<(), Error<I2C::Error>> {
+    let mut attempt = 1;
+    let result = loop {
+      match self.read_once(module, register, value, delay_us) {
+        Err(_) if attempt < self.retry.attempts => {
+          attempt += 1;
+          self.stats.record_retry();
+          self.delay.delay_us(self.retry.backoff_us);
+        }
+        result => break result,
+      }
+    };
+
+    self.count(result)
+  }
+
+  /// Writes up to 31 bytes to `register` of the seesaw `module`, retrying as the
+  /// retry policy says. Longer values fail with `Error::OutOfBounds`.
+  pub fn write(&mut self, module: u8, register: u8, value: &[u8]) -> Result<(), Error<I2C::Error>> {
+    if value.len() > MAX_WRITE_LENGTH {
+      return Err(Error::OutOfBounds);
+    }
+
+    let mut attempt = 1;
+    let result = loop {
+      match self.write_once(module, register, value) {
+        Err(_) if attempt < self.retry.attempts => {
+          attempt += 1;
+          self.stats.record_retry();
+          self.delay.delay_us(self.retry.backoff_us);
+        }
+        result => break result,
+      }
+    };
+
+    self.count(result)
+  }
+
+  fn count(&mut self, result: Result<(), Error<I2C::Error>>) -> Result<(), Error<I2C::Error>> {
+    self.errors = if result.is_ok() {
+      0
+    } else {
+      self.errors.saturating_add(1)
+    };
+    result
+  }
+
+  pub(crate) fn read_once(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &mut [u8],
+    delay_us: u32,
+  ) -> Result<(), Error<I2C::Error>> {
+    let address = self.address;
+    let result = match self.bus.write(address, &[module, register]) {
+      Ok(()) => {
+        self.delay.delay_us(delay_us);
+        self
+          .bus
+          .read(address, value)
+          .map_err(|error| Error::ReadError {
+            address,
+            module,
+            register,
+            error,
+          })
+      }
+      Err(error) => Err(Error::WriteError {
+        address,
+        module,
+        register,
+        error,
+      }),
+    };
+    self.stats.record(true, 2 + value.len(), &result);
+    trace!(
+      "seesaw {:#x} read {:#x}/{:#x}: {:?} {:?}",
+      address,
+      module,
+      register,
+      value,
+      result
+    );
+
+    result
+  }
+
+  fn write_once(
+    &mut self,
+    module: u8,
+    register: u8,
+    value: &[u8],
+  ) -> Result<(), Error<I2C::Error>> {
+    assert!(value.len() <= MAX_WRITE_LENGTH);
+    let mut command = [0u8; 34];
+    command[0] = module;
+    command[1] = register;
+    command[2..(2 + value.len())].copy_from_slice(value);
+    let result = self
+      .bus
+      .write(self.address, &command[0..(2 + value.len())])
+      .map_err(|error| Error::WriteError {
+        address: self.address,
+        module,
+        register,
+        error,
+      });
+    self.stats.record(false, 2 + value.len(), &result);
+    trace!(
+      "seesaw {:#x} write {:#x}/{:#x}: {:?} {:?}",
+      self.address,
+      module,
+      register,
+      value,
+      result
+    );
+
+    result
+  }
+
+  /// Whether the chip answers with the seesaw hardware ID, without resetting it.
+  pub(crate) fn is_present(&mut self) -> bool {
+    let mut id = [0u8];
+    let result = self.read_once(Module::Status.into(), STATUS_HW_ID, &mut id, READ_DELAY_US);
+
+    result.is_ok() && HardwareId::try_from(id[0]).is_ok()
+  }
+
+  /// Reads which seesaw chip this is.
+  pub fn hardware_id(&mut self) -> Result<HardwareId, Error<I2C::Error>> {
+    let mut id = [0u8];
+    self.read(Module::Status.into(), STATUS_HW_ID, &mut id)?;
+
+    HardwareId::try_from(id[0]).map_err(|_| Error::WrongChipId {
+      address: self.address,
+      id: id[0],
+    })
+  }
+
+  /// Reads the product ID and build date of the seesaw firmware.
+  pub fn firmware_version(&mut self) -> Result<FirmwareVersion, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self.read(Module::Status.into(), STATUS_VERSION, &mut value)?;
+
+    Ok(FirmwareVersion::from_register(u32::from_be_bytes(value)))
+  }
+
+  /// Reads which seesaw modules the firmware implements.
+  pub fn capabilities(&mut self) -> Result<Capabilities, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self.read(Module::Status.into(), STATUS_OPTIONS, &mut value)?;
+
+    Ok(Capabilities::from_register(u32::from_be_bytes(value)))
+  }
+
+  /// Reads the temperature of the seesaw chip.
+  pub fn temperature(&mut self) -> Result<Temperature, Error<I2C::Error>> {
+    let mut value = [0u8; 4];
+    self.read(Module::Status.into(), STATUS_TEMP, &mut value)?;
+
+    Ok(Temperature::from_register(u32::from_be_bytes(value)))
+  }
+
+  pub fn address(&self) -> Address {
+    Address::new(self.address)
+  }
+
+  /// Register accesses that failed in a row, after their retries.
+  pub fn errors(&self) -> u8 {
+    self.errors
+  }
+
+  /// Bus traffic of this chip, see `Stats`.
+  pub fn stats(&self) -> Stats {
+    self.stats
+  }
+
+  pub fn reset_stats(&mut self) {
+    self.stats = Stats::new();
+  }
+
+  pub(crate) fn delay(&mut self) -> &mut D {
+    &mut self.delay
+  }
+
+  /// Sets how long `soft_reset` waits for the chip to come back before failing.
+  /// The chip is polled, so the reset finishes as soon as it answers. 500 ms by
+  /// default.
+  pub fn set_reset_timeout(&mut self, timeout_ms: u32) {
+    self.reset_timeout_ms = timeout_ms;
+  }
+
+  pub fn reset_timeout(&self) -> u32 {
+    self.reset_timeout_ms
+  }
+
+  /// Sets how failed register reads and writes are retried, by default they aren't.
+  pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+    self.retry = policy;
+  }
+
+  pub fn retry_policy(&self) -> RetryPolicy {
+    self.retry
+  }
+
+  /// Gives back the bus and the delay, e.g. to hand the bus to another driver.
+  pub fn free(self) -> (I2C, D) {
+    (self.bus, self.delay)
+  }
+}
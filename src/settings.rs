@@ -0,0 +1,71 @@
+use crate::{ColorOrder, Rotation};
+
+/// EEPROM offset of the settings, well below the address byte at the end.
+pub(crate) const SETTINGS_OFFSET: u8 = 0;
+pub(crate) const SETTINGS_LENGTH: usize = 6;
+
+const MAGIC: u8 = 0x4e;
+const VERSION: u8 = 1;
+
+const ROTATIONS: [Rotation; 4] = [
+  Rotation::Deg0,
+  Rotation::Deg90,
+  Rotation::Deg180,
+  Rotation::Deg270,
+];
+
+const COLOR_ORDERS: [ColorOrder; 8] = [
+  ColorOrder::Grb,
+  ColorOrder::Rgb,
+  ColorOrder::Rbg,
+  ColorOrder::Gbr,
+  ColorOrder::Brg,
+  ColorOrder::Bgr,
+  ColorOrder::Grbw,
+  ColorOrder::Rgbw,
+];
+
+/// Settings a board keeps in the seesaw EEPROM across power cycles, see
+/// `NeoTrellis::save_settings`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+  pub brightness: u8,
+  pub rotation: Rotation,
+  pub color_order: ColorOrder,
+}
+
+impl Settings {
+  pub(crate) fn encode(&self) -> [u8; SETTINGS_LENGTH] {
+    let mut blob = [
+      MAGIC,
+      VERSION,
+      self.brightness,
+      self.rotation as u8,
+      self.color_order as u8,
+      0,
+    ];
+    blob[SETTINGS_LENGTH - 1] = checksum(&blob[..SETTINGS_LENGTH - 1]);
+
+    blob
+  }
+
+  /// `None` for an EEPROM that holds no settings, e.g. a new board.
+  pub(crate) fn decode(blob: &[u8; SETTINGS_LENGTH]) -> Option<Self> {
+    if blob[0] != MAGIC
+      || blob[1] != VERSION
+      || blob[SETTINGS_LENGTH - 1] != checksum(&blob[..SETTINGS_LENGTH - 1])
+    {
+      return None;
+    }
+
+    Some(Self {
+      brightness: blob[2],
+      rotation: *ROTATIONS.get(usize::from(blob[3]))?,
+      color_order: *COLOR_ORDERS.get(usize::from(blob[4]))?,
+    })
+  }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+  !bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
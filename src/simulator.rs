@@ -0,0 +1,257 @@
+//! A grid of boards simulated in a terminal, to develop the UI logic of an
+//! application without hardware.
+//!
+//! `SimTrellis` has the LED and key event methods of `MultiTrellis` and implements
+//! `TrellisDevice`. `show` draws the grid with 24 bit color escape codes, key
+//! presses come from `press`, `release` or from keyboard characters passed to
+//! `feed`:
+//!
+//! ```ignore
+//! let mut trellis = SimTrellis::<1, 1>::new();
+//! for line in std::io::stdin().lines() {
+//!   trellis.feed(&line?);
+//!   trellis.drain_events(|event| app.handle(event))?;
+//!   app.draw(&mut trellis)?;
+//!   trellis.show()?;
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::{Color, Event, Framebuffer, MultiEvent, TrellisDevice};
+
+/// Keyboard characters of the keys of a 4x4 grid, row by row.
+pub const QWERTY: [&str; 4] = ["1234", "qwer", "asdf", "zxcv"];
+
+/// Errors of the simulator, which has no bus and no board address.
+#[derive(Debug)]
+pub enum SimError {
+  /// Writing to the terminal failed.
+  Io(io::Error),
+  /// A key or LED position outside of the grid.
+  OutOfBounds,
+}
+
+impl fmt::Display for SimError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SimError::Io(error) => write!(f, "terminal output failed: {}", error),
+      SimError::OutOfBounds => f.write_str("out of bounds"),
+    }
+  }
+}
+
+impl std::error::Error for SimError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      SimError::Io(error) => Some(error),
+      SimError::OutOfBounds => None,
+    }
+  }
+}
+
+pub struct SimTrellis<const W: usize = 1, const H: usize = 1> {
+  pixels: Framebuffer<W, H>,
+  brightness: u8,
+  /// Held keys, one bit per key of every row.
+  held: [u64; 64],
+  events: VecDeque<MultiEvent>,
+  keyboard: &'static [&'static str],
+  output: Box<dyn Write>,
+  /// Lines drawn by the last `show`, which the next one draws over.
+  drawn_lines: usize,
+}
+
+impl<const W: usize, const H: usize> SimTrellis<W, H> {
+  /// A simulator drawing to stdout, with the `QWERTY` keyboard map.
+  pub fn new() -> Self {
+    Self::with_output(Box::new(io::stdout()))
+  }
+
+  /// A simulator drawing to `output`, e.g. a file or a buffer in tests.
+  pub fn with_output(output: Box<dyn Write>) -> Self {
+    assert!(4 * W <= 64 && 4 * H <= 64);
+    Self {
+      pixels: Framebuffer::new(),
+      brightness: 255,
+      held: [0; 64],
+      events: VecDeque::new(),
+      keyboard: &QWERTY,
+      output,
+      drawn_lines: 0,
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    4 * W
+  }
+
+  pub fn height(&self) -> usize {
+    4 * H
+  }
+
+  /// Sets the color of the LED at `index`, fails with `SimError::OutOfBounds` outside
+  /// of the grid.
+  pub fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), SimError> {
+    self.contains(index)?;
+    self.pixels.set(index.0, index.1, color);
+
+    Ok(())
+  }
+
+  /// Color of the LED at `index` as last set, before the brightness is applied.
+  pub fn led_color(&self, index: (u8, u8)) -> Option<Color> {
+    self.pixels.get(index.0, index.1)
+  }
+
+  pub fn set_brightness(&mut self, brightness: u8) {
+    self.brightness = brightness;
+  }
+
+  pub fn brightness(&self) -> u8 {
+    self.brightness
+  }
+
+  pub fn fill(&mut self, color: Color) {
+    self.pixels.fill(color);
+  }
+
+  pub fn clear(&mut self) {
+    self.fill(Color::BLACK);
+  }
+
+  /// Draws the grid over the previous drawing, two character cells per key.
+  pub fn show(&mut self) -> Result<(), SimError> {
+    let mut frame = String::new();
+    if self.drawn_lines > 0 {
+      frame.push_str(&format!("\x1b[{}A", self.drawn_lines));
+    }
+    for y in 0..self.height() as u8 {
+      for x in 0..self.width() as u8 {
+        let color = self
+          .pixels
+          .get(x, y)
+          .unwrap_or_default()
+          .scale(self.brightness);
+        frame.push_str(&format!("\x1b[48;2;{};{};{}m  ", color.r, color.g, color.b));
+      }
+      frame.push_str("\x1b[0m\n");
+    }
+
+    self
+      .output
+      .write_all(frame.as_bytes())
+      .and_then(|()| self.output.flush())
+      .map_err(SimError::Io)?;
+    self.drawn_lines = self.height();
+
+    Ok(())
+  }
+
+  /// Presses the key at `index`, queueing a rising edge unless it is held already.
+  pub fn press(&mut self, index: (u8, u8)) -> Result<(), SimError> {
+    self.contains(index)?;
+    if !self.is_held(index) {
+      self.held[usize::from(index.1)] |= 1 << index.0;
+      self.queue(index, Event::Rising);
+    }
+
+    Ok(())
+  }
+
+  /// Releases the key at `index`, queueing a falling edge if it was held.
+  pub fn release(&mut self, index: (u8, u8)) -> Result<(), SimError> {
+    self.contains(index)?;
+    if self.is_held(index) {
+      self.held[usize::from(index.1)] &= !(1 << index.0);
+      self.queue(index, Event::Falling);
+    }
+
+    Ok(())
+  }
+
+  pub fn is_held(&self, index: (u8, u8)) -> bool {
+    self.contains(index).is_ok() && self.held[usize::from(index.1)] & (1 << index.0) != 0
+  }
+
+  /// Changes which keyboard characters `feed` maps to keys, one string per row.
+  pub fn set_keyboard_map(&mut self, rows: &'static [&'static str]) {
+    self.keyboard = rows;
+  }
+
+  /// Presses and releases the key of every character of `input` in the keyboard
+  /// map, returning the number of keys pressed. Other characters are ignored.
+  pub fn feed(&mut self, input: &str) -> usize {
+    let mut pressed = 0;
+    for c in input.chars() {
+      if let Some(index) = self.key_of(c) {
+        if self.press(index).is_ok() && self.release(index).is_ok() {
+          pressed += 1;
+        }
+      }
+    }
+
+    pressed
+  }
+
+  /// Passes every queued key event to `handler`, returning the number of events.
+  pub fn drain_events<F: FnMut(MultiEvent)>(&mut self, mut handler: F) -> Result<usize, SimError> {
+    let count = self.events.len();
+    for event in self.events.drain(..) {
+      handler(event);
+    }
+
+    Ok(count)
+  }
+
+  fn key_of(&self, c: char) -> Option<(u8, u8)> {
+    self.keyboard.iter().enumerate().find_map(|(y, row)| {
+      let x = row.chars().position(|key| key == c)?;
+      Some((x as u8, y as u8))
+    })
+  }
+
+  fn contains(&self, index: (u8, u8)) -> Result<(), SimError> {
+    if usize::from(index.0) < self.width() && usize::from(index.1) < self.height() {
+      Ok(())
+    } else {
+      Err(SimError::OutOfBounds)
+    }
+  }
+
+  fn queue(&mut self, coordinate: (u8, u8), event: Event) {
+    self.events.push_back(MultiEvent { coordinate, event });
+  }
+}
+
+impl<const W: usize, const H: usize> Default for SimTrellis<W, H> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<const W: usize, const H: usize> TrellisDevice for SimTrellis<W, H> {
+  type Error = SimError;
+
+  fn size(&self) -> (u8, u8) {
+    (self.width() as u8, self.height() as u8)
+  }
+
+  fn set_led_color(&mut self, index: (u8, u8), color: Color) -> Result<(), SimError> {
+    SimTrellis::set_led_color(self, index, color)
+  }
+
+  fn fill(&mut self, color: Color) {
+    SimTrellis::fill(self, color);
+  }
+
+  fn show(&mut self) -> Result<(), SimError> {
+    SimTrellis::show(self)
+  }
+
+  fn drain_events<F: FnMut(MultiEvent)>(&mut self, handler: F) -> Result<usize, SimError> {
+    SimTrellis::drain_events(self, handler)
+  }
+}
@@ -0,0 +1,201 @@
+use core::cell::{Cell, UnsafeCell};
+
+use critical_section::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::{Color, Error, Event, EventProducer, Framebuffer, Key, KeypadEvent, NeoTrellis};
+
+/// A driver shared by a `KeypadHalf` and a `NeopixelHalf`, e.g. to read the keys
+/// from an interrupt handler while the main loop animates the LEDs.
+///
+/// A half locks the board for the duration of a call, taking a critical section
+/// only to flip the lock, so bus transfers and delays run with interrupts enabled.
+/// A call while the other half holds the lock fails with `Error::Busy`. The
+/// storage is `Sync` when the bus and the delay are `Send`, so it can live in a
+/// `static`:
+///
+/// ```ignore
+/// static TRELLIS: StaticCell<SharedTrellis<I2C, Delay>> = StaticCell::new();
+/// let shared = TRELLIS.init(SharedTrellis::new(NeoTrellis::new(i2c, Address::DEFAULT, delay)?));
+/// let (keypad, neopixel) = shared.split();
+/// ```
+pub struct SharedTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  trellis: UnsafeCell<NeoTrellis<I2C, D>>,
+  locked: Mutex<Cell<bool>>,
+}
+
+// The lock hands out at most one `&mut NeoTrellis` at a time, see `with`.
+unsafe impl<I2C, D> Sync for SharedTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+  NeoTrellis<I2C, D>: Send,
+{
+}
+
+impl<I2C, D> SharedTrellis<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  pub const fn new(trellis: NeoTrellis<I2C, D>) -> Self {
+    Self {
+      trellis: UnsafeCell::new(trellis),
+      locked: Mutex::new(Cell::new(false)),
+    }
+  }
+
+  pub fn split(&self) -> (KeypadHalf<'_, I2C, D>, NeopixelHalf<'_, I2C, D>) {
+    (KeypadHalf { shared: self }, NeopixelHalf { shared: self })
+  }
+
+  pub fn into_inner(self) -> NeoTrellis<I2C, D> {
+    self.trellis.into_inner()
+  }
+
+  fn with<R>(
+    &self,
+    f: impl FnOnce(&mut NeoTrellis<I2C, D>) -> Result<R, Error<I2C::Error>>,
+  ) -> Result<R, Error<I2C::Error>> {
+    if critical_section::with(|cs| self.locked.borrow(cs).replace(true)) {
+      return Err(Error::Busy);
+    }
+    let _unlock = Unlock(&self.locked);
+    // SAFETY: `locked` was false and is now true until `_unlock` is dropped, so no
+    // other reference to the driver exists.
+    f(unsafe { &mut *self.trellis.get() })
+  }
+}
+
+/// Releases the lock of a `SharedTrellis`, also when the call panics.
+struct Unlock<'a>(&'a Mutex<Cell<bool>>);
+
+impl Drop for Unlock<'_> {
+  fn drop(&mut self) {
+    critical_section::with(|cs| self.0.borrow(cs).set(false));
+  }
+}
+
+/// The keypad of a `SharedTrellis`, see the `NeoTrellis` methods of the same name.
+pub struct KeypadHalf<'a, I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  shared: &'a SharedTrellis<I2C, D>,
+}
+
+impl<'a, I2C, D> KeypadHalf<'a, I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  pub fn set_key_event(
+    &mut self,
+    key: Key,
+    event: Event,
+    enable: bool,
+  ) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.set_key_event(key, event, enable))
+  }
+
+  pub fn enable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.enable_interrupt())
+  }
+
+  pub fn disable_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.disable_interrupt())
+  }
+
+  pub fn keypad_count(&mut self) -> Result<u8, Error<I2C::Error>> {
+    self.shared.with(|t| t.keypad_count())
+  }
+
+  pub fn read_key_events(
+    &mut self,
+    events: &mut [Option<KeypadEvent>],
+  ) -> Result<usize, Error<I2C::Error>> {
+    self.shared.with(|t| t.read_key_events(events))
+  }
+
+  pub fn drain_events<F: FnMut(KeypadEvent)>(
+    &mut self,
+    handler: F,
+  ) -> Result<usize, Error<I2C::Error>> {
+    self.shared.with(|t| t.drain_events(handler))
+  }
+
+  pub fn pump<const N: usize>(
+    &mut self,
+    queue: &mut EventProducer<'_, N>,
+  ) -> Result<usize, Error<I2C::Error>> {
+    self.shared.with(|t| t.pump(queue))
+  }
+}
+
+/// The LEDs of a `SharedTrellis`, see the `NeoTrellis` methods of the same name.
+pub struct NeopixelHalf<'a, I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  shared: &'a SharedTrellis<I2C, D>,
+}
+
+impl<'a, I2C, D> NeopixelHalf<'a, I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  pub fn set_led_color(&mut self, led: u8, color: Color) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.set_led_color(led, color))
+  }
+
+  pub fn set_led_colors(&mut self, colors: &[Color; 16]) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.set_led_colors(colors))
+  }
+
+  pub fn fill(&mut self, color: Color) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| {
+      t.fill(color);
+      Ok(())
+    })
+  }
+
+  pub fn clear(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| {
+      t.clear();
+      Ok(())
+    })
+  }
+
+  pub fn set_brightness(&mut self, brightness: u8) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| {
+      t.set_brightness(brightness);
+      Ok(())
+    })
+  }
+
+  pub fn brightness(&self) -> Result<u8, Error<I2C::Error>> {
+    self.shared.with(|t| Ok(t.brightness()))
+  }
+
+  pub fn set_pixel_brightness(&mut self, led: u8, brightness: u8) -> Result<(), Error<I2C::Error>> {
+    self
+      .shared
+      .with(|t| t.set_pixel_brightness(led, brightness))
+  }
+
+  pub fn show_led(&mut self) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.show_led())
+  }
+
+  pub fn flush(&mut self, framebuffer: &Framebuffer) -> Result<(), Error<I2C::Error>> {
+    self.shared.with(|t| t.flush(framebuffer))
+  }
+}
@@ -0,0 +1,51 @@
+/// Which seesaw modules of the board a `NeoTrellis` state has set up.
+pub trait State {
+  const KEYPAD: bool;
+  const NEOPIXEL: bool;
+}
+
+/// States in which the keypad methods are available.
+pub trait HasKeypad: State {}
+
+/// States in which the LED methods are available.
+pub trait HasNeopixel: State {}
+
+/// The driver was created but the board wasn't reset and set up yet, see
+/// `NeoTrellis::uninitialized`.
+pub struct Uninitialized;
+
+/// The board was reset and both the keypad and the neopixel module are set up.
+pub struct Ready;
+
+/// Only the keypad is set up, for a board used purely as a button matrix, see
+/// `NeoTrellis::init_keypad`.
+pub struct KeypadOnly;
+
+/// Only the neopixel module is set up, for a board used purely as a light panel,
+/// see `NeoTrellis::init_neopixel`.
+pub struct NeopixelOnly;
+
+impl State for Uninitialized {
+  const KEYPAD: bool = false;
+  const NEOPIXEL: bool = false;
+}
+
+impl State for Ready {
+  const KEYPAD: bool = true;
+  const NEOPIXEL: bool = true;
+}
+
+impl State for KeypadOnly {
+  const KEYPAD: bool = true;
+  const NEOPIXEL: bool = false;
+}
+
+impl State for NeopixelOnly {
+  const KEYPAD: bool = false;
+  const NEOPIXEL: bool = true;
+}
+
+impl HasKeypad for Ready {}
+impl HasKeypad for KeypadOnly {}
+impl HasNeopixel for Ready {}
+impl HasNeopixel for NeopixelOnly {}
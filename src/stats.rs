@@ -0,0 +1,59 @@
+use core::ops::Add;
+
+/// Bus traffic of one board, counted since the driver was created or since
+/// `reset_stats`. The counters wrap around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+  /// Register reads, each one a write of the register address and a read.
+  pub reads: u32,
+  pub writes: u32,
+  /// Bytes on the bus, including the module and register bytes of every access.
+  pub bytes: u32,
+  /// Accesses repeated because of the retry policy.
+  pub retries: u32,
+  /// Accesses that failed, including the ones that succeeded on a retry.
+  pub errors: u32,
+}
+
+impl Stats {
+  pub const fn new() -> Self {
+    Self {
+      reads: 0,
+      writes: 0,
+      bytes: 0,
+      retries: 0,
+      errors: 0,
+    }
+  }
+
+  pub(crate) fn record<T, E>(&mut self, read: bool, bytes: usize, result: &Result<T, E>) {
+    if read {
+      self.reads = self.reads.wrapping_add(1);
+    } else {
+      self.writes = self.writes.wrapping_add(1);
+    }
+    self.bytes = self.bytes.wrapping_add(bytes as u32);
+    if result.is_err() {
+      self.errors = self.errors.wrapping_add(1);
+    }
+  }
+
+  pub(crate) fn record_retry(&mut self) {
+    self.retries = self.retries.wrapping_add(1);
+  }
+}
+
+/// Sums the counters, e.g. of all boards of a grid.
+impl Add for Stats {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Self {
+      reads: self.reads.wrapping_add(other.reads),
+      writes: self.writes.wrapping_add(other.writes),
+      bytes: self.bytes.wrapping_add(other.bytes),
+      retries: self.retries.wrapping_add(other.retries),
+      errors: self.errors.wrapping_add(other.errors),
+    }
+  }
+}
@@ -0,0 +1,118 @@
+/// Contents of the seesaw version register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FirmwareVersion {
+  /// Adafruit product ID of the firmware, 3954 for the NeoTrellis.
+  pub product_id: u16,
+  /// Build date, see `year`, `month` and `day`.
+  pub date_code: u16,
+}
+
+impl FirmwareVersion {
+  pub(crate) const fn from_register(value: u32) -> Self {
+    Self {
+      product_id: (value >> 16) as u16,
+      date_code: value as u16,
+    }
+  }
+
+  pub const fn year(&self) -> u16 {
+    2000 + (self.date_code & 0x3f)
+  }
+
+  pub const fn month(&self) -> u8 {
+    ((self.date_code >> 7) & 0x0f) as u8
+  }
+
+  pub const fn day(&self) -> u8 {
+    ((self.date_code >> 11) & 0x1f) as u8
+  }
+}
+
+/// Seesaw modules implemented by the firmware, from the options register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+  pub(crate) const fn from_register(value: u32) -> Self {
+    Self(value)
+  }
+
+  /// The raw options bitmask, bit `n` is set if the module at base `n` exists.
+  pub const fn bits(&self) -> u32 {
+    self.0
+  }
+
+  const fn has(&self, base: u8) -> bool {
+    self.0 & (1 << base) != 0
+  }
+
+  pub const fn gpio(&self) -> bool {
+    self.has(0x01)
+  }
+
+  pub const fn sercom(&self) -> bool {
+    self.has(0x02)
+  }
+
+  /// PWM outputs.
+  pub const fn timer(&self) -> bool {
+    self.has(0x08)
+  }
+
+  pub const fn adc(&self) -> bool {
+    self.has(0x09)
+  }
+
+  pub const fn dac(&self) -> bool {
+    self.has(0x0A)
+  }
+
+  pub const fn interrupt(&self) -> bool {
+    self.has(0x0B)
+  }
+
+  pub const fn eeprom(&self) -> bool {
+    self.has(0x0D)
+  }
+
+  pub const fn neopixel(&self) -> bool {
+    self.has(0x0E)
+  }
+
+  pub const fn touch(&self) -> bool {
+    self.has(0x0F)
+  }
+
+  pub const fn keypad(&self) -> bool {
+    self.has(0x10)
+  }
+
+  pub const fn encoder(&self) -> bool {
+    self.has(0x11)
+  }
+}
+
+/// Chip temperature as a 16.16 fixed point number of degrees Celsius.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Temperature(u32);
+
+impl Temperature {
+  /// The upper two bits of the register are not part of the value.
+  pub(crate) const fn from_register(value: u32) -> Self {
+    Self(value & 0x3fff_ffff)
+  }
+
+  /// The 16.16 fixed point value.
+  pub const fn raw(&self) -> u32 {
+    self.0
+  }
+
+  /// Whole degrees Celsius, truncated.
+  pub const fn celsius(&self) -> u16 {
+    (self.0 >> 16) as u16
+  }
+
+  pub const fn millicelsius(&self) -> u32 {
+    ((self.0 as u64 * 1000) >> 16) as u32
+  }
+}
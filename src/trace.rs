@@ -0,0 +1,14 @@
+//! Register access logging of the `trace` feature, through `defmt` when that feature
+//! is enabled and through `log` otherwise.
+
+#[cfg(all(feature = "trace", not(any(feature = "defmt", feature = "log"))))]
+compile_error!("the `trace` feature logs through `defmt` or `log`, enable one of them");
+
+macro_rules! trace {
+  ($($arg:tt)*) => {
+    #[cfg(all(feature = "trace", feature = "defmt"))]
+    defmt::trace!($($arg)*);
+    #[cfg(all(feature = "trace", feature = "log", not(feature = "defmt")))]
+    log::trace!($($arg)*);
+  };
+}
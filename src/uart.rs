@@ -0,0 +1,87 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::Seesaw;
+use crate::{Error, Module};
+
+const SERCOM_STATUS: u8 = 0x00;
+const SERCOM_INTEN: u8 = 0x02;
+const SERCOM_INTENCLR: u8 = 0x03;
+const SERCOM_BAUD: u8 = 0x04;
+const SERCOM_DATA: u8 = 0x05;
+
+/// `SERCOM_STATUS` bit set while received data is waiting.
+const STATUS_DATA_READY: u8 = 0x01;
+
+/// Bytes sent by each bus write.
+const UART_CHUNK: usize = 31;
+/// SERCOM modules of the seesaw register map.
+const SERCOMS: u8 = 6;
+/// The SERCOM registers answer right away, unlike the keypad a read waits 6 ms for.
+const SERCOM_READ_DELAY_US: u32 = 250;
+
+/// UARTs of the seesaw SERCOM modules, so a seesaw board can bridge I2C to a serial
+/// device, e.g. a GPS or a DMX feed. `sercom` numbers the SERCOM of the chip, from
+/// 0 to 5, the firmware decides which ones run as UART. Other numbers fail with
+/// `Error::OutOfBounds`.
+impl<I2C, D> Seesaw<I2C, D>
+where
+  I2C: I2c,
+  D: DelayNs,
+{
+  pub fn uart_set_baud(&mut self, sercom: u8, baud: u32) -> Result<(), Error<I2C::Error>> {
+    self.write(sercom_module(sercom)?, SERCOM_BAUD, &baud.to_be_bytes())
+  }
+
+  /// Sends `data` through the UART.
+  pub fn uart_write(&mut self, sercom: u8, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+    let module = sercom_module(sercom)?;
+    for chunk in data.chunks(UART_CHUNK) {
+      self.write(module, SERCOM_DATA, chunk)?;
+    }
+
+    Ok(())
+  }
+
+  /// Reads the received bytes into `buffer` until it is full or no more bytes are
+  /// waiting, returning how many were read.
+  ///
+  /// The SERCOM has no count of waiting bytes, every byte takes a status and a data
+  /// read, without the long wait of the keypad reads.
+  pub fn uart_read(&mut self, sercom: u8, buffer: &mut [u8]) -> Result<usize, Error<I2C::Error>> {
+    let module = sercom_module(sercom)?;
+    for (count, byte) in buffer.iter_mut().enumerate() {
+      let mut status = [0u8];
+      self.read_delayed(module, SERCOM_STATUS, &mut status, SERCOM_READ_DELAY_US)?;
+      if status[0] & STATUS_DATA_READY == 0 {
+        return Ok(count);
+      }
+
+      let byte = core::slice::from_mut(byte);
+      self.read_delayed(module, SERCOM_DATA, byte, SERCOM_READ_DELAY_US)?;
+    }
+
+    Ok(buffer.len())
+  }
+
+  /// Makes the chip pull its INT line low while received data is waiting.
+  pub fn uart_enable_interrupt(&mut self, sercom: u8) -> Result<(), Error<I2C::Error>> {
+    self.write(sercom_module(sercom)?, SERCOM_INTEN, &[STATUS_DATA_READY])
+  }
+
+  pub fn uart_disable_interrupt(&mut self, sercom: u8) -> Result<(), Error<I2C::Error>> {
+    self.write(
+      sercom_module(sercom)?,
+      SERCOM_INTENCLR,
+      &[STATUS_DATA_READY],
+    )
+  }
+}
+
+fn sercom_module<E>(sercom: u8) -> Result<u8, Error<E>> {
+  if sercom >= SERCOMS {
+    return Err(Error::OutOfBounds);
+  }
+
+  Ok(u8::from(Module::Sercom0) + sercom)
+}
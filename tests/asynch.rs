@@ -0,0 +1,110 @@
+//! Byte level checks of the async driver, against a mocked I2C bus.
+
+#![cfg(feature = "async")]
+
+mod common;
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use common::*;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use neotrellis::asynch::{MultiTrellis, NeoTrellis};
+use neotrellis::{Address, Color, Event, Layout, Placement, Rotation};
+
+/// Runs `future` to completion, the mocked bus never makes it wait.
+fn block_on<F: Future>(future: F) -> F::Output {
+  fn raw_waker() -> RawWaker {
+    fn clone(_: *const ()) -> RawWaker {
+      raw_waker()
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    RawWaker::new(core::ptr::null(), &VTABLE)
+  }
+
+  let waker = unsafe { Waker::from_raw(raw_waker()) };
+  let mut context = Context::from_waker(&waker);
+  let mut future = pin!(future);
+  loop {
+    if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+      return output;
+    }
+  }
+}
+
+/// A driver set up by `NeoTrellis::new`, expecting `transactions` afterwards.
+fn trellis(transactions: &[Transaction]) -> NeoTrellis<Mock, NoopDelay> {
+  let mut expectations = init();
+  expectations.extend_from_slice(transactions);
+
+  block_on(NeoTrellis::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  ))
+  .unwrap()
+}
+
+fn done(trellis: NeoTrellis<Mock, NoopDelay>) {
+  let (mut bus, _) = trellis.free();
+  bus.done();
+}
+
+#[test]
+fn grids_rotate_the_boards_of_their_layout() {
+  let mut left = vec![write(&[NEOPIXEL, 0x05])];
+  left.extend(read(KEYPAD, 0x04, &[0]));
+  // The top left key of the right board, turned clockwise, is physical key 12 at
+  // byte 36. Physical key 3 is its bottom right key.
+  let mut right = vec![
+    write(&[NEOPIXEL, 0x04, 0x00, 0x24, 0x02, 0x01, 0x03]),
+    write(&[NEOPIXEL, 0x05]),
+  ];
+  right.extend(drain(&[0x0F]));
+
+  let layout = Layout::custom([
+    [Placement::new(0, 0)],
+    [Placement::new(4, 0).rotated(Rotation::Deg90)],
+  ]);
+  let mut grid = MultiTrellis::with_layout([[trellis(&left)], [trellis(&right)]], layout);
+
+  grid.set_led_color((4, 0), Color::rgb(1, 2, 3)).unwrap();
+  block_on(grid.show()).unwrap();
+  let mut events = Vec::new();
+  block_on(grid.drain_events(|event| events.push(event))).unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0].coordinate, (7, 3));
+  assert_eq!(events[0].event, Event::Rising);
+
+  let [[left], [right]] = grid.into_inner();
+  done(left);
+  done(right);
+}
+
+#[test]
+fn events_and_poll_event_decode_the_fifo() {
+  // Seesaw key 9 is row 1, column 1: key 5
+  let mut expectations = read(KEYPAD, 0x04, &[2]);
+  expectations.extend(read(KEYPAD, 0x10, &[0x27, 0x26]));
+  expectations.extend(read(KEYPAD, 0x04, &[1]));
+  expectations.extend(read(KEYPAD, 0x10, &[0x03]));
+  expectations.extend(read(KEYPAD, 0x04, &[0]));
+  let mut trellis = trellis(&expectations);
+
+  let mut keys = Vec::new();
+  let mut events = trellis.events();
+  while let Some(event) = block_on(events.next()) {
+    let event = event.unwrap();
+    keys.push((event.key.index(), event.event));
+  }
+  assert_eq!(keys, [(5, Event::Rising), (5, Event::Falling)]);
+
+  let event = block_on(trellis.poll_event()).unwrap().unwrap();
+  assert_eq!((event.key.index(), event.event), (0, Event::Rising));
+  assert!(block_on(trellis.poll_event()).unwrap().is_none());
+
+  done(trellis);
+}
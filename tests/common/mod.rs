@@ -0,0 +1,104 @@
+//! Expected seesaw bus traffic of the NeoTrellis driver, as `embedded-hal-mock`
+//! transactions.
+
+#![allow(dead_code)]
+
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use neotrellis::{Address, NeoTrellis, State};
+
+pub const ADDRESS: u8 = 0x2E;
+
+pub const STATUS: u8 = 0x00;
+pub const NEOPIXEL: u8 = 0x0E;
+pub const KEYPAD: u8 = 0x10;
+
+/// Seesaw hardware ID of the SAMD09 on the NeoTrellis.
+pub const SAMD09: u8 = 0x55;
+
+/// Key command disabling the level events of a key, `(High | Low) << 1`.
+pub const DISABLE_LEVELS: u8 = 0x06;
+/// Key command enabling the edge events of a key, `(Rising | Falling) << 1 | 1`.
+pub const ENABLE_EDGES: u8 = 0x19;
+
+pub fn write(bytes: &[u8]) -> Transaction {
+  Transaction::write(ADDRESS, bytes.to_vec())
+}
+
+/// A register read: the module and register are written, the value is read after
+/// a pause.
+pub fn read(module: u8, register: u8, response: &[u8]) -> Vec<Transaction> {
+  vec![
+    write(&[module, register]),
+    Transaction::read(ADDRESS, response.to_vec()),
+  ]
+}
+
+/// Software reset, answered right away by the hardware ID poll.
+pub fn reset() -> Vec<Transaction> {
+  let mut transactions = vec![write(&[STATUS, 0x7F, 0xFF])];
+  transactions.extend(read(STATUS, 0x01, &[SAMD09]));
+  transactions
+}
+
+/// Neopixel pin 3 and a buffer of 16 pixels of `bytes_per_pixel` bytes.
+pub fn setup_neopixel(bytes_per_pixel: u16) -> Vec<Transaction> {
+  let length = (16 * bytes_per_pixel).to_be_bytes();
+  vec![
+    write(&[NEOPIXEL, 0x01, 0x03]),
+    write(&[NEOPIXEL, 0x03, length[0], length[1]]),
+  ]
+}
+
+/// Key commands for all 16 keys, written in chunks of 15 commands.
+pub fn setup_keypad(disable: u8, enable: u8) -> Vec<Transaction> {
+  let mut commands = Vec::new();
+  for key in 0..16u8 {
+    let wire_key = ((key & 0x0C) << 1) | (key & 0x03);
+    commands.extend_from_slice(&[wire_key, disable, wire_key, enable]);
+  }
+
+  commands
+    .chunks(30)
+    .map(|chunk| {
+      let mut bytes = vec![KEYPAD, 0x01];
+      bytes.extend_from_slice(chunk);
+      write(&bytes)
+    })
+    .collect()
+}
+
+/// Everything `NeoTrellis::new` sends.
+pub fn init() -> Vec<Transaction> {
+  let mut transactions = reset();
+  transactions.extend(setup_neopixel(3));
+  transactions.extend(setup_keypad(DISABLE_LEVELS, ENABLE_EDGES));
+  transactions
+}
+
+/// Keypad FIFO read of `fifo`, followed by the empty count `drain_events` stops at.
+pub fn drain(fifo: &[u8]) -> Vec<Transaction> {
+  let mut transactions = read(KEYPAD, 0x04, &[fifo.len() as u8]);
+  transactions.extend(read(KEYPAD, 0x10, fifo));
+  transactions.extend(read(KEYPAD, 0x04, &[0]));
+  transactions
+}
+
+/// A driver set up by `NeoTrellis::new`, expecting `transactions` afterwards.
+pub fn trellis(transactions: &[Transaction]) -> NeoTrellis<Mock, NoopDelay> {
+  let mut expectations = init();
+  expectations.extend_from_slice(transactions);
+
+  NeoTrellis::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  )
+  .unwrap()
+}
+
+/// Checks that the driver sent all expected transactions.
+pub fn done<S: State>(trellis: NeoTrellis<Mock, NoopDelay, S>) {
+  let (mut bus, _) = trellis.free();
+  bus.done();
+}
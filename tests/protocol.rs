@@ -0,0 +1,417 @@
+//! Byte level checks of the seesaw protocol, against a mocked I2C bus.
+
+mod common;
+
+use common::*;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use neotrellis::{
+  Address, Color, ColorOrder, Config, Encoder, Error, Event, EventSet, Key, KeypadEvent, Layout,
+  MultiTrellis, NeoTrellis, Placement, RetryPolicy, Rotation, Seesaw, SeesawNeoPixel,
+  TrellisDevice,
+};
+
+#[test]
+fn new_resets_and_sets_up_both_modules() {
+  done(trellis(&[]));
+}
+
+#[test]
+fn reset_polls_until_the_chip_answers() {
+  let mut expectations = vec![
+    write(&[STATUS, 0x7F, 0xFF]),
+    write(&[STATUS, 0x01]).with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+  ];
+  expectations.extend(read(STATUS, 0x01, &[0x00]));
+  expectations.extend(read(STATUS, 0x01, &[SAMD09]));
+
+  let mut seesaw = Seesaw::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  );
+  seesaw.soft_reset().unwrap();
+
+  seesaw.free().0.done();
+}
+
+#[test]
+fn config_sets_buffer_length_and_key_events() {
+  let mut expectations = setup_neopixel(4);
+  // (Rising | Falling) << 1 disabled, (High | Low) << 1 | 1 enabled
+  expectations.extend(setup_keypad(0x18, 0x07));
+
+  let config = Config {
+    color_order: ColorOrder::Grbw,
+    key_events: EventSet::LEVELS,
+    reset: false,
+    ..Config::new(Address::new(ADDRESS))
+  };
+  let trellis =
+    NeoTrellis::with_config(Mock::new(&expectations), NoopDelay::new(), config).unwrap();
+
+  done(trellis);
+}
+
+#[test]
+fn show_led_writes_changed_pixels_in_grb_order() {
+  let mut trellis = trellis(&[
+    // Buffer offset 10 * 3 bytes, big endian
+    write(&[NEOPIXEL, 0x04, 0x00, 0x1E, 0x02, 0x01, 0x03]),
+    write(&[NEOPIXEL, 0x05]),
+    // Nothing changed
+    write(&[NEOPIXEL, 0x05]),
+  ]);
+
+  trellis.set_led_color(10, Color::rgb(1, 2, 3)).unwrap();
+  trellis.show_led().unwrap();
+  trellis.show_led().unwrap();
+
+  done(trellis);
+}
+
+#[test]
+fn fill_splits_pixels_into_writes_of_29_bytes() {
+  let mut first = vec![NEOPIXEL, 0x04, 0x00, 0x00];
+  first.extend([0x00, 0xFF, 0x00].repeat(9));
+  let mut second = vec![NEOPIXEL, 0x04, 0x00, 0x1B];
+  second.extend([0x00, 0xFF, 0x00].repeat(7));
+
+  let mut trellis = trellis(&[write(&first), write(&second), write(&[NEOPIXEL, 0x05])]);
+
+  trellis.fill(Color::RED);
+  trellis.show_led().unwrap();
+
+  done(trellis);
+}
+
+#[test]
+fn brightness_scales_the_written_colors() {
+  // A new brightness resends every pixel
+  let mut first = vec![NEOPIXEL, 0x04, 0x00, 0x00, 0x40, 0x7F, 0x00];
+  first.extend([0x00; 24]);
+  let mut second = vec![NEOPIXEL, 0x04, 0x00, 0x1B];
+  second.extend([0x00; 21]);
+
+  let mut trellis = trellis(&[write(&first), write(&second), write(&[NEOPIXEL, 0x05])]);
+
+  trellis.set_brightness(127);
+  trellis
+    .set_led_color(0, Color::rgb(0xFF, 0x80, 0x00))
+    .unwrap();
+  trellis.show_led().unwrap();
+
+  done(trellis);
+}
+
+#[test]
+fn drain_events_decodes_the_fifo() {
+  // Seesaw key 9 is row 1, column 1: key 5
+  let mut trellis = trellis(&drain(&[0x27, 0xFF, 0x26]));
+
+  let mut events = Vec::new();
+  let count = trellis.drain_events(|event| events.push(event)).unwrap();
+
+  assert_eq!(count, 2);
+  assert_eq!(events.len(), 2);
+  assert_eq!(events[0].key.index(), 5);
+  assert_eq!(events[0].event, Event::Rising);
+  assert_eq!(events[1].key.index(), 5);
+  assert_eq!(events[1].event, Event::Falling);
+
+  done(trellis);
+}
+
+#[test]
+fn read_key_events_reads_at_most_the_slots() {
+  let mut expectations = read(KEYPAD, 0x04, &[3]);
+  expectations.extend(read(KEYPAD, 0x10, &[0x03, 0x6B]));
+  let mut trellis = trellis(&expectations);
+
+  let mut events = [None; 2];
+  assert_eq!(trellis.read_key_events(&mut events).unwrap(), 2);
+  // Seesaw key 0, and key 26 in row 3 and column 2: key 14
+  assert_eq!(events[0].unwrap().key.index(), 0);
+  assert_eq!(events[1].unwrap().key.index(), 14);
+  assert_eq!(events[1].unwrap().event, Event::Rising);
+
+  done(trellis);
+}
+
+#[test]
+fn invalid_fifo_bytes_are_protocol_errors() {
+  // Seesaw key 4 is column 4, which the 4x4 matrix doesn't have
+  let mut trellis = trellis(&drain(&[0x10])[..4]);
+
+  let result = trellis.drain_events(|_| {});
+  assert!(matches!(
+    result,
+    Err(Error::ProtocolError {
+      address: ADDRESS,
+      byte: 0x10
+    })
+  ));
+
+  done(trellis);
+}
+
+#[test]
+fn bus_errors_name_the_failing_access() {
+  let mut trellis = trellis(&[
+    write(&[KEYPAD, 0x04]).with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+  ]);
+
+  let error = trellis.keypad_count().unwrap_err();
+  assert!(matches!(
+    error,
+    Error::WriteError {
+      address: ADDRESS,
+      module: KEYPAD,
+      register: 0x04,
+      ..
+    }
+  ));
+  assert_eq!(
+    error.kind(),
+    Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+  );
+
+  done(trellis);
+}
+
+#[test]
+fn failed_writes_are_retried() {
+  let show = [NEOPIXEL, 0x05];
+  let mut trellis = trellis(&[write(&show).with_error(ErrorKind::Bus), write(&show)]);
+
+  trellis.set_retry_policy(RetryPolicy::new(2, 0));
+  trellis.reset_stats();
+  trellis.show_led().unwrap();
+
+  let stats = trellis.stats();
+  assert_eq!(stats.writes, 2);
+  assert_eq!(stats.retries, 1);
+  assert_eq!(stats.errors, 1);
+  assert_eq!(stats.bytes, 4);
+
+  done(trellis);
+}
+
+#[test]
+fn disabled_keys_report_no_events() {
+  // Key 5 is seesaw key 9, all of its events are disabled
+  let mut trellis = trellis(&[write(&[KEYPAD, 0x01, 0x09, 0x1E, 0x09, 0x01])]);
+
+  trellis.disable_key(Key::from_index(5)).unwrap();
+  assert!(!trellis.key_enabled(Key::from_index(5)));
+
+  done(trellis);
+}
+
+#[test]
+fn neopixel_strip_offsets_are_big_endian() {
+  let expectations = [
+    write(&[NEOPIXEL, 0x01, 0x0A]),
+    // 120 pixels * 3 bytes
+    write(&[NEOPIXEL, 0x03, 0x01, 0x68]),
+    // Pixel 100 starts at byte 300
+    write(&[NEOPIXEL, 0x04, 0x01, 0x2C, 0x00, 0x00, 0xFF]),
+    write(&[NEOPIXEL, 0x05]),
+  ];
+  let seesaw = Seesaw::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  );
+  let mut strip = SeesawNeoPixel::from_seesaw(seesaw, 10, 120, ColorOrder::Grb).unwrap();
+
+  strip.write(100, &[Color::BLUE]).unwrap();
+  strip.show().unwrap();
+  assert!(matches!(
+    strip.write(120, &[Color::BLUE]),
+    Err(Error::OutOfBounds)
+  ));
+
+  let (mut bus, _) = strip.free();
+  bus.done();
+}
+
+#[test]
+fn probe_treats_a_nack_as_absent() {
+  let expectations = [Transaction::write(0x30, vec![STATUS, 0x01])
+    .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))];
+  let mut bus = Mock::new(&expectations);
+
+  let present = Seesaw::probe(&mut bus, Address::new(0x30), &mut NoopDelay::new()).unwrap();
+  assert!(!present);
+
+  bus.done();
+}
+
+#[test]
+fn device_coordinates_map_to_led_indices() {
+  fn draw<T: TrellisDevice>(device: &mut T) -> Result<(), T::Error> {
+    device.set_led_color((1, 1), Color::rgb(1, 2, 3))?;
+    device.show()
+  }
+
+  // LED 5 starts at byte 15
+  let mut trellis = trellis(&[
+    write(&[NEOPIXEL, 0x04, 0x00, 0x0F, 0x02, 0x01, 0x03]),
+    write(&[NEOPIXEL, 0x05]),
+  ]);
+
+  draw(&mut trellis).unwrap();
+  assert!(matches!(
+    TrellisDevice::set_led_color(&mut trellis, (4, 0), Color::RED),
+    Err(Error::OutOfBounds)
+  ));
+
+  done(trellis);
+}
+
+#[test]
+fn touch_channels_are_range_checked() {
+  let expectations = read(0x0F, 0x13, &[0x01, 0x2C]);
+  let mut seesaw = Seesaw::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  );
+
+  assert_eq!(seesaw.touch_read(3).unwrap(), 300);
+  assert!(matches!(seesaw.touch_read(4), Err(Error::OutOfBounds)));
+  assert!(matches!(seesaw.touch_read(250), Err(Error::OutOfBounds)));
+
+  seesaw.free().0.done();
+}
+
+#[test]
+fn samd09_pwm_pins_map_to_timer_outputs() {
+  let mut expectations = read(STATUS, 0x01, &[SAMD09]);
+  // Pin 5 is PWM output 1
+  expectations.push(write(&[0x08, 0x01, 0x01, 0x80, 0x00]));
+  expectations.extend(read(STATUS, 0x01, &[SAMD09]));
+  let mut seesaw = Seesaw::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  );
+
+  seesaw.set_pwm_duty(5, 0x8000).unwrap();
+  assert!(matches!(
+    seesaw.set_pwm_frequency(3, 440),
+    Err(Error::OutOfBounds)
+  ));
+
+  seesaw.free().0.done();
+}
+
+#[test]
+fn uart_reads_until_no_data_is_waiting() {
+  let mut expectations = read(0x03, 0x00, &[0x01]);
+  expectations.extend(read(0x03, 0x05, b"$"));
+  expectations.extend(read(0x03, 0x00, &[0x01]));
+  expectations.extend(read(0x03, 0x05, b"G"));
+  expectations.extend(read(0x03, 0x00, &[0x00]));
+  let mut seesaw = Seesaw::new(
+    Mock::new(&expectations),
+    Address::new(ADDRESS),
+    NoopDelay::new(),
+  );
+
+  let mut buffer = [0u8; 8];
+  assert_eq!(seesaw.uart_read(1, &mut buffer).unwrap(), 2);
+  assert_eq!(&buffer[..2], b"$G");
+  // SERCOM 6 would be the timer module
+  assert!(matches!(
+    seesaw.uart_read(6, &mut buffer),
+    Err(Error::OutOfBounds)
+  ));
+
+  seesaw.free().0.done();
+}
+
+#[test]
+fn oversized_raw_writes_are_rejected() {
+  let mut value = vec![NEOPIXEL, 0x04];
+  value.extend([0xAA; 31]);
+  let mut trellis = trellis(&[write(&value)]);
+
+  trellis.write_raw(NEOPIXEL, 0x04, &[0xAA; 31]).unwrap();
+  assert!(matches!(
+    trellis.write_raw(NEOPIXEL, 0x04, &[0xAA; 32]),
+    Err(Error::OutOfBounds)
+  ));
+
+  done(trellis);
+}
+
+#[test]
+fn grids_rotate_the_boards_of_their_layout() {
+  let mut left = vec![write(&[NEOPIXEL, 0x05])];
+  left.extend(read(KEYPAD, 0x04, &[0]));
+  // The top left key of the right board, turned clockwise, is physical key 12 at
+  // byte 36. Physical key 3 is its bottom right key.
+  let mut right = vec![
+    write(&[NEOPIXEL, 0x04, 0x00, 0x24, 0x02, 0x01, 0x03]),
+    write(&[NEOPIXEL, 0x05]),
+  ];
+  right.extend(drain(&[0x0F]));
+
+  let layout = Layout::custom([
+    [Placement::new(0, 0)],
+    [Placement::new(4, 0).rotated(Rotation::Deg90)],
+  ]);
+  let mut grid = MultiTrellis::with_layout([[trellis(&left)], [trellis(&right)]], layout);
+
+  grid.set_led_color((4, 0), Color::rgb(1, 2, 3)).unwrap();
+  grid.show().unwrap();
+  let mut events = Vec::new();
+  grid.drain_events(|event| events.push(event)).unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0].coordinate, (7, 3));
+  assert_eq!(events[0].event, Event::Rising);
+
+  let [[left], [right]] = grid.into_inner();
+  done(left);
+  done(right);
+}
+
+#[test]
+fn read_key_events_keeps_the_events_around_a_corrupted_byte() {
+  let mut expectations = read(KEYPAD, 0x04, &[3]);
+  expectations.extend(read(KEYPAD, 0x10, &[0x03, 0x10, 0x6B]));
+  let mut trellis = trellis(&expectations);
+
+  let mut events = [None; 64];
+  events[5] = Some(KeypadEvent {
+    key: Key::from_index(1),
+    event: Event::Rising,
+  });
+  let result = trellis.read_key_events(&mut events);
+  assert!(matches!(
+    result,
+    Err(Error::ProtocolError { byte: 0x10, .. })
+  ));
+  assert_eq!(events[0].unwrap().key.index(), 0);
+  assert_eq!(events[1].unwrap().key.index(), 14);
+  assert!(events[2..].iter().all(Option::is_none));
+
+  done(trellis);
+}
+
+#[test]
+fn encoder_button_pins_are_range_checked() {
+  let mut bus = Mock::new(&[]);
+  let result = Encoder::new(
+    bus.clone(),
+    Encoder::<Mock, NoopDelay>::DEFAULT_ADDRESS,
+    NoopDelay::new(),
+    32,
+  );
+  assert!(matches!(result, Err(Error::OutOfBounds)));
+
+  bus.done();
+}